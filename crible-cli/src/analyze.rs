@@ -0,0 +1,61 @@
+use std::io::{stdout, Write};
+
+use anyhow::{anyhow, Result};
+
+use crible_core::{Analysis, GameRecord};
+
+/// Decisions losing less than this many expected points aren't worth showing.
+const THRESHOLD: f32 = 0.05;
+
+/// Print the decisions of the game saved at `path` that lost expected points, and the total lost
+/// by each player.
+pub fn run(path: &str, samples: u32, all: bool) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|err| anyhow!("can't read {path}: {err}"))?;
+    let record: GameRecord = text.parse()?;
+    let names = record.player_names();
+
+    let analysis = Analysis::new(&record, samples)?;
+
+    let mut out = stdout().lock();
+    for decision in &analysis.decisions {
+        if !all && decision.loss() < THRESHOLD {
+            continue;
+        }
+        write!(
+            out,
+            "Hand {:>2}, {}: {} ({:+.1})",
+            decision.hand, names[decision.player], decision.actual, decision.actual_value
+        )?;
+        match decision.loss() < THRESHOLD {
+            true => writeln!(out, ", best")?,
+            false => writeln!(
+                out,
+                ", best {} ({:+.1}), lost {:.1}",
+                decision.best,
+                decision.best_value,
+                decision.loss()
+            )?,
+        }
+    }
+
+    writeln!(out, "\nExpected points lost:")?;
+    for (player, name) in names.iter().enumerate() {
+        let decisions = analysis
+            .decisions
+            .iter()
+            .filter(|decision| decision.player == player);
+        let mistakes = decisions
+            .clone()
+            .filter(|decision| decision.loss() >= THRESHOLD)
+            .count();
+        writeln!(
+            out,
+            "  {name}: {:.1} in {} mistakes over {} decisions",
+            analysis.loss(player),
+            mistakes,
+            decisions.count()
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,112 +1,333 @@
-use std::io::{stdout, Write};
+use std::io::{stdin, stdout, IsTerminal, Write};
 
-use anyhow::Result;
-use itertools::Itertools;
+use anyhow::{anyhow, Result};
 
 use crible_core::*;
 
-struct Scores {
-    scores: [(Card, u8); 52 - 4],
-    len: u8,
-    sorted: bool,
-}
+mod analyze;
+mod matches;
+mod play;
+mod repl;
+mod replay;
+mod report;
+mod squares;
+mod tournament;
+
+use report::{write_ranking, ReportOptions};
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1).peekable();
 
-impl Scores {
-    fn new() -> Self {
-        Scores {
-            scores: [(Card::new(Number::A, Suit::H), 0); 52 - 4],
-            len: 0,
-            sorted: true,
+    match args.peek().map(String::as_str) {
+        Some("repl") => {
+            args.next();
+            let mut style = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--cards" => style = Some(flag_value(&mut args, "--cards")?.parse()?),
+                    _ => return Err(anyhow!("unexpected argument: {arg}")),
+                }
+            }
+            repl::run(style.unwrap_or_else(default_style))
         }
-    }
+        Some("play") => {
+            args.next();
+            let mut style = None;
+            let mut seed = None;
+            let mut save = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--cards" => style = Some(flag_value(&mut args, "--cards")?.parse()?),
+                    "--seed" => seed = Some(flag_value(&mut args, "--seed")?.parse()?),
+                    "--save" => save = Some(flag_value(&mut args, "--save")?),
+                    _ => return Err(anyhow!("unexpected argument: {arg}")),
+                }
+            }
+            play::run(
+                style.unwrap_or_else(default_style),
+                seed.unwrap_or_else(rand::random),
+                save.as_deref(),
+            )
+        }
+        Some("squares") => {
+            args.next();
+            let mut style = None;
+            let mut seed = None;
+            let mut depth = 1;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--cards" => style = Some(flag_value(&mut args, "--cards")?.parse()?),
+                    "--seed" => seed = Some(flag_value(&mut args, "--seed")?.parse()?),
+                    "--depth" => {
+                        let n = flag_value(&mut args, "--depth")?;
+                        depth = n.parse().map_err(|_| anyhow!("invalid --depth: {n}"))?;
+                    }
+                    _ => return Err(anyhow!("unexpected argument: {arg}")),
+                }
+            }
+            squares::run(
+                style.unwrap_or_else(default_style),
+                seed.unwrap_or_else(rand::random),
+                depth,
+            )
+        }
+        Some("analyze") => {
+            args.next();
+            let mut samples = 200;
+            let mut all = false;
+            let mut path = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--samples" => {
+                        let n = flag_value(&mut args, "--samples")?;
+                        samples = n.parse().map_err(|_| anyhow!("invalid --samples: {n}"))?;
+                    }
+                    "--all" => all = true,
+                    _ => path = Some(arg),
+                }
+            }
+            let path = path.ok_or_else(|| anyhow!("analyze expects a game record file"))?;
+            analyze::run(&path, samples, all)
+        }
+        Some("odds") => {
+            args.next();
+            let mut scores = Vec::new();
+            let mut own_deals = false;
+            for arg in args {
+                match arg.as_str() {
+                    "--dealer" => own_deals = true,
+                    "--pone" => own_deals = false,
+                    _ => scores.push(
+                        arg.parse::<u8>()
+                            .map_err(|_| anyhow!("invalid score: {arg}"))?,
+                    ),
+                }
+            }
+            let [own, opponent] = scores[..] else {
+                return Err(anyhow!("odds expects 2 scores, yours first"));
+            };
+            if own.max(opponent) > GAME_POINTS {
+                return Err(anyhow!("scores go up to {GAME_POINTS}"));
+            }
 
-    fn push(&mut self, card: Card, score: u8) {
-        self.sorted = false;
-        self.scores[self.len as usize] = (card, score);
-        self.len += 1;
-    }
+            let odds = WinTable::standard().win_probability(own, opponent, own_deals);
+            let role = if own_deals { "dealing" } else { "pone" };
+            println!("At {own}-{opponent}, {role}: {:.1}% to win.", 100.0 * odds);
+            Ok(())
+        }
+        Some("replay") => {
+            args.next();
+            let mut style = None;
+            let mut path = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--cards" => style = Some(flag_value(&mut args, "--cards")?.parse()?),
+                    _ => path = Some(arg),
+                }
+            }
+            let path = path.ok_or_else(|| anyhow!("replay expects a game record file"))?;
+            replay::run(&path, style.unwrap_or_else(default_style))
+        }
+        Some("tournament") => {
+            args.next();
+            let mut games = 100;
+            let mut seed = 0;
+            let mut names = Vec::new();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--games" => {
+                        let n = flag_value(&mut args, "--games")?;
+                        games = n.parse().map_err(|_| anyhow!("invalid --games: {n}"))?;
+                    }
+                    "--seed" => seed = flag_value(&mut args, "--seed")?.parse()?,
+                    _ => names.push(arg),
+                }
+            }
+            if names.is_empty() {
+                names = PLAYER_NAMES.iter().map(|name| name.to_string()).collect();
+            }
 
-    fn sort(&mut self) {
-        if !self.sorted {
-            self.scores[0..self.len as usize].sort_by(|(_, a), (_, b)| b.cmp(a));
-            self.sorted = true;
+            let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+            let pairs = (names.len() * (names.len() - 1) / 2) as u32;
+            let tournament = Tournament::new(&names, games, seed)?;
+            tournament::run(&tournament, games * pairs)
         }
-    }
+        Some("match") => {
+            args.next();
+            let mut length = MatchLength::FirstTo(7);
+            let mut seed = None;
+            let mut save = None;
+            let mut names = Vec::new();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--length" => length = flag_value(&mut args, "--length")?.parse()?,
+                    "--seed" => seed = Some(flag_value(&mut args, "--seed")?.parse()?),
+                    "--save" => save = Some(flag_value(&mut args, "--save")?),
+                    _ => names.push(arg),
+                }
+            }
+            let [a, b] = names.as_slice() else {
+                return Err(anyhow!("a match needs 2 players"));
+            };
 
-    fn mean(&self) -> f32 {
-        self.scores
-            .iter()
-            .map(|(_, score)| *score as u32)
-            .sum::<u32>() as f32
-            / self.len as f32
-    }
+            matches::run(
+                [a, b],
+                length,
+                seed.unwrap_or_else(rand::random),
+                save.as_deref(),
+            )
+        }
+        Some("engine") => {
+            args.next();
+            let mut name = String::from("bot");
+            let mut seed = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--seed" => seed = Some(flag_value(&mut args, "--seed")?.parse()?),
+                    _ => name = arg,
+                }
+            }
 
-    fn iter(&self) -> impl Iterator<Item = (Card, u8)> + '_ {
-        self.scores[0..self.len as usize].iter().copied()
+            let player = new_player(&name, seed.unwrap_or_else(rand::random))?;
+            Engine::new(&format!("crible {name}"), player).run(stdin().lock(), stdout())
+        }
+        _ => discard(args),
     }
 }
 
-fn main() -> Result<()> {
-    let input: String = std::env::args().skip(1).collect();
+/// Print the best discards for the cards given as arguments.
+fn discard(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let mut input = String::new();
+    let mut exclude = String::new();
+    let mut opts = ReportOptions::default();
+    let mut style = None;
+    let mut scores = None;
+    let mut is_dealer = None;
+    let mut owns_crib = None;
+    let mut kept = 4;
+    let mut objective = Objective::Most;
+    let mut jokers = 0;
 
-    let mut deck = Deck::new_shuffled();
-    let mut cards = parse_cards(input.as_str())?;
-    cards.sort();
-    deck.remove(&cards);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--exclude" => {
+                exclude.push_str(&flag_value(&mut args, "--exclude")?);
+                exclude.push(' ');
+            }
+            "--top" => {
+                let n = flag_value(&mut args, "--top")?;
+                opts.top_n = Some(n.parse().map_err(|_| anyhow!("invalid --top: {n}"))?);
+            }
+            "--all" => opts.top_n = None,
+            "--all-starters" => opts.max_starters = None,
+            "--by-rank" => opts.by_rank = true,
+            "--scores" => {
+                let value = flag_value(&mut args, "--scores")?;
+                let invalid = || anyhow!("invalid --scores, expected <mine>-<theirs>: {value}");
+                let (own, opponent) = value.split_once('-').ok_or_else(invalid)?;
+                scores = Some(Scores {
+                    own: own.parse().map_err(|_| invalid())?,
+                    opponent: opponent.parse().map_err(|_| invalid())?,
+                });
+            }
+            "--dealer" => is_dealer = Some(true),
+            "--pone" => is_dealer = Some(false),
+            "--crib" => {
+                owns_crib = match flag_value(&mut args, "--crib")?.as_str() {
+                    "mine" => Some(true),
+                    "theirs" => Some(false),
+                    other => {
+                        return Err(anyhow!("invalid --crib, expected mine or theirs: {other}"))
+                    }
+                }
+            }
+            "--keep" => {
+                let n = flag_value(&mut args, "--keep")?;
+                kept = n.parse().map_err(|_| anyhow!("invalid --keep: {n}"))?;
+            }
+            "--lowball" => objective = Objective::Fewest,
+            "--jokers" => {
+                let n = flag_value(&mut args, "--jokers")?;
+                jokers = n.parse().map_err(|_| anyhow!("invalid --jokers: {n}"))?;
+            }
+            "--cards" => style = Some(flag_value(&mut args, "--cards")?.parse()?),
+            _ => input.push_str(&arg),
+        }
+    }
 
-    let mut results: Vec<(Hand, Scores)> = Vec::new();
+    opts.style = style.unwrap_or_else(default_style);
 
-    // All possible combinaisons of 4 cards
-    for (c1, c2, c3, c4) in cards.iter().copied().tuple_combinations() {
-        let hand = Hand::from_array([c1, c2, c3, c4]);
+    let mut cards = parse_cards(input.as_str())?;
+    cards.sort();
+    let mut known = parse_cards(exclude.as_str())?;
+    known.sort();
 
-        let mut scores = Scores::new();
+    let dealt_jokers = cards
+        .iter()
+        .copied()
+        .filter(|card| card.is_joker())
+        .collect::<Vec<_>>();
+    if jokers == 0 && !dealt_jokers.is_empty() {
+        let hint = if dealt_jokers.len() == 1 {
+            "1 or 2"
+        } else {
+            "2"
+        };
+        return Err(anyhow!(
+            "{} dealt, but the deck has no jokers: pass --jokers {hint}",
+            opts.style.cards(&dealt_jokers)
+        ));
+    }
 
-        for starter in deck.cards().copied() {
-            let score = hand.score(starter, false);
-            scores.push(starter, score);
+    let mut ranking =
+        DiscardRanking::with_jokers(&cards, &known, kept, jokers)?.with_objective(objective);
+    let position = match (scores, is_dealer) {
+        (Some(scores), Some(is_dealer)) => Some(Position { scores, is_dealer }),
+        (None, None) => None,
+        _ => return Err(anyhow!("--scores goes with --dealer or --pone")),
+    };
+    match (position, owns_crib) {
+        (Some(_), Some(_)) => return Err(anyhow!("--crib doesn't go with --scores")),
+        (Some(position), None) => {
+            let model = WinTable::standard();
+            ranking.rank_by_win(&cards, position, model, 1000, &mut rand::thread_rng())?;
         }
-        scores.sort();
-
-        results.push((hand, scores))
+        (None, Some(owns_crib)) => {
+            ranking.rank_with_crib(&cards, owns_crib, 2000, &mut rand::thread_rng())?;
+        }
+        (None, None) => {}
     }
 
-    results.sort_by(|(_, a), (_, b)| b.mean().partial_cmp(&a.mean()).unwrap());
-
     let mut lock = stdout().lock();
     writeln!(
         lock,
         "What's the best play for {}?\n",
-        cards.iter().join(" ")
+        opts.style.cards(&cards)
     )?;
+    if !known.is_empty() {
+        writeln!(lock, "Known cards: {}\n", opts.style.cards(&known))?;
+    }
+    if let Some(Position { scores, is_dealer }) = position {
+        let role = if is_dealer { "dealer" } else { "pone" };
+        writeln!(
+            lock,
+            "Playing for the win at {}-{} as {role}.\n",
+            scores.own, scores.opponent
+        )?;
+    }
 
-    let top_n = 4;
-    for (hand, scores) in results.iter().take(top_n) {
-        let mut top_starters: Vec<(u8, Vec<Card>)> = Default::default();
-        for (score, chunks) in &scores.iter().chunk_by(|(_, score)| *score) {
-            let mut starters = chunks.map(|(card, _)| card).collect::<Vec<_>>();
-            starters.sort();
-            top_starters.push((score, starters));
-        }
+    write_ranking(&mut lock, &ranking, &opts)
+}
 
-        writeln!(lock, "Hand: {hand}  Mean: {:.2}", scores.mean())?;
-        writeln!(lock, "  Top starters: ")?;
-        for (score, starters) in top_starters {
-            write!(lock, "      {: >2} points: ", score)?;
-            write!(lock, "{}", starters.iter().take(10).join(" "))?;
-            if starters.len() > 10 {
-                write!(lock, " ...")?;
-            }
-            write!(lock, "\n")?;
-        }
-        writeln!(lock, "")?;
-    }
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().ok_or_else(|| anyhow!("{flag} expects a value"))
+}
 
-    match results.len().saturating_sub(top_n) {
-        0 => {} // no-op
-        1 => println!("... and 1 worse hand"),
-        n => println!("... and {n} worse hands"),
+/// Color when printing to a terminal, plain glyphs when piped.
+fn default_style() -> CardStyle {
+    if stdout().is_terminal() {
+        CardStyle::Color
+    } else {
+        CardStyle::Unicode
     }
-
-    Ok(())
 }
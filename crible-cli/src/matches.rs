@@ -0,0 +1,65 @@
+use std::io::{stdout, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crible_core::{new_player, Match, MatchGame, MatchLength, Skunk};
+
+/// Play a match between the strategies `names`, printing each game as it ends. With `save`, each
+/// game record is written to that directory as `game-N.txt`.
+pub fn run(names: [&str; 2], length: MatchLength, seed: u64, save: Option<&str>) -> Result<()> {
+    let mut game_match = Match::new(length, seed)?;
+    let players = [new_player(names[0], seed)?, new_player(names[1], !seed)?];
+
+    let mut out = stdout().lock();
+    let mut error = None;
+    let winner = game_match.play([&*players[0], &*players[1]], |game| {
+        if let Err(err) = write_game(&mut out, names, game) {
+            error.get_or_insert(err);
+        }
+    })?;
+    if let Some(err) = error {
+        return Err(err);
+    }
+
+    let score = game_match.score();
+    writeln!(
+        out,
+        "{} wins the match {}-{}",
+        names[winner],
+        score[winner],
+        score[1 - winner]
+    )?;
+
+    if let Some(dir) = save {
+        std::fs::create_dir_all(dir).map_err(|err| anyhow!("can't create {dir}: {err}"))?;
+        for (n, game) in game_match.games().iter().enumerate() {
+            let mut record = game.record.clone();
+            record.set_tag("Player0", names[0]);
+            record.set_tag("Player1", names[1]);
+            let path = Path::new(dir).join(format!("game-{}.txt", n + 1));
+            std::fs::write(&path, record.to_string())
+                .map_err(|err| anyhow!("can't save the game to {}: {err}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_game(w: &mut impl Write, names: [&str; 2], game: &MatchGame) -> Result<()> {
+    let scores = game.record.scores();
+    let skunk = match game.skunk {
+        Skunk::None => "",
+        Skunk::Single => ", skunk",
+        Skunk::Double => ", double skunk",
+    };
+    writeln!(
+        w,
+        "Game {}: {} wins {}-{}{skunk}",
+        game.record.tag("Game").unwrap_or("?"),
+        names[game.winner],
+        scores[game.winner],
+        scores[1 - game.winner],
+    )?;
+    Ok(())
+}
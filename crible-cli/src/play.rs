@@ -0,0 +1,436 @@
+use std::io::{stdout, Write};
+
+use anyhow::{anyhow, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, queue};
+
+use crible_core::*;
+
+const HUMAN: usize = 0;
+const BOT: usize = 1;
+const LOG_LINES: usize = 6;
+
+/// Keys the table reacts to, decoupled from the terminal so it can be driven in tests.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    Left,
+    Right,
+    Select,
+    Enter,
+    Go,
+    Quit,
+}
+
+/// A game against the bot, as seen from the human player's seat.
+pub struct Table {
+    game: Game,
+    bot: Bot,
+    style: CardStyle,
+    cursor: usize,
+    selected: Vec<Card>,
+    log: Vec<String>,
+    message: Option<String>,
+}
+
+pub fn run(style: CardStyle, seed: u64, save: Option<&str>) -> Result<()> {
+    let mut table = Table::new(Game::new(seed), style);
+
+    let mut out = stdout();
+    terminal::enable_raw_mode()?;
+    queue!(out, EnterAlternateScreen, cursor::Hide)?;
+
+    let result = (|| -> Result<()> {
+        loop {
+            queue!(out, cursor::MoveTo(0, 0), Clear(ClearType::All))?;
+            write!(out, "{}", table.render().join("\r\n"))?;
+            out.flush()?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let key = match key.code {
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                KeyCode::Char(' ') => Key::Select,
+                KeyCode::Enter => Key::Enter,
+                KeyCode::Char('g') => Key::Go,
+                KeyCode::Char('q') | KeyCode::Esc => Key::Quit,
+                _ => continue,
+            };
+
+            if !table.handle(key) {
+                return Ok(());
+            }
+        }
+    })();
+
+    queue!(out, cursor::Show, LeaveAlternateScreen)?;
+    out.flush()?;
+    terminal::disable_raw_mode()?;
+
+    if let Some(path) = save {
+        let mut record = table.game.record().clone();
+        record.set_tag("Player0", "you");
+        record.set_tag("Player1", "bot");
+        std::fs::write(path, record.to_string())
+            .map_err(|err| anyhow!("can't save the game to {path}: {err}"))?;
+    }
+
+    result
+}
+
+impl Table {
+    pub fn new(game: Game, style: CardStyle) -> Self {
+        let mut table = Self {
+            game,
+            bot: Bot,
+            style,
+            cursor: 0,
+            selected: Vec::with_capacity(2),
+            log: Vec::new(),
+            message: None,
+        };
+        table.log_deal();
+        table
+    }
+
+    /// React to a key press, returns false to leave the table.
+    pub fn handle(&mut self, key: Key) -> bool {
+        self.message = None;
+        if key == Key::Quit {
+            return false;
+        }
+
+        let hand_len = self.human_cards().len();
+        match key {
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(hand_len.saturating_sub(1)),
+            _ => {}
+        }
+
+        let result = match (self.game.phase(), key) {
+            (Phase::Discard, Key::Select) => {
+                self.toggle_selected();
+                Ok(())
+            }
+            (Phase::Discard, Key::Enter) => self.discard(),
+            (Phase::Pegging, Key::Enter) => self.play_selected(),
+            (Phase::Pegging, Key::Go) => self.peg(PegAction::Go),
+            (Phase::Show, Key::Enter) => self.next_hand(),
+            (Phase::Over, Key::Enter) => return false,
+            _ => Ok(()),
+        };
+
+        if let Err(err) = result {
+            self.message = Some(err.to_string());
+        }
+        true
+    }
+
+    /// Cards shown in the human's hand: the deal, or what's left to play.
+    fn human_cards(&self) -> &[Card] {
+        match (self.game.phase(), self.game.pegging()) {
+            (Phase::Pegging, Some(pegging)) => pegging.hand(HUMAN),
+            _ => self.game.hand(HUMAN),
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        let Some(card) = self.human_cards().get(self.cursor).copied() else {
+            return;
+        };
+
+        if let Some(idx) = self.selected.iter().position(|c| *c == card) {
+            self.selected.remove(idx);
+        } else if self.selected.len() < 2 {
+            self.selected.push(card);
+        } else {
+            self.message = Some("Only 2 cards go to the crib".to_string());
+        }
+    }
+
+    fn discard(&mut self) -> Result<()> {
+        if self.selected.len() != 2 {
+            self.message = Some("Select 2 cards for the crib with space".to_string());
+            return Ok(());
+        }
+
+        let mut points = self.game.discard(HUMAN, &self.selected)?;
+        let bot_discard = self.bot.discard(&self.game.discard_state(BOT))?;
+        points.extend(self.game.discard(BOT, &bot_discard)?);
+
+        self.selected.clear();
+        self.cursor = 0;
+        if let Some(starter) = self.game.starter() {
+            self.log
+                .push(format!("Starter: {}", self.style.card(starter)));
+        }
+        self.log_points(&points);
+        self.bot_turns()
+    }
+
+    fn play_selected(&mut self) -> Result<()> {
+        let Some(pegging) = self.game.pegging() else {
+            return Ok(());
+        };
+        if pegging.to_play() != Some(HUMAN) {
+            return Ok(());
+        }
+
+        if !pegging.can_play(HUMAN) {
+            return self.peg(PegAction::Go);
+        }
+        match pegging.hand(HUMAN).get(self.cursor) {
+            Some(card) => self.peg(PegAction::Play(*card)),
+            None => Ok(()),
+        }
+    }
+
+    fn peg(&mut self, action: PegAction) -> Result<()> {
+        self.act(HUMAN, action)?;
+        self.cursor = self.cursor.min(self.human_cards().len().saturating_sub(1));
+        self.bot_turns()
+    }
+
+    fn act(&mut self, player: usize, action: PegAction) -> Result<()> {
+        let count = self.game.pegging().map(Pegging::count).unwrap_or(0);
+        let points = self.game.peg(player, action)?;
+
+        let who = name(player);
+        match action {
+            PegAction::Play(card) => self.log.push(format!(
+                "{who} {} {} ({})",
+                if player == HUMAN { "play" } else { "plays" },
+                self.style.card(card),
+                count + card.value()
+            )),
+            PegAction::Go => self.log.push(format!("{who}: go")),
+        }
+        self.log_points(&points);
+
+        Ok(())
+    }
+
+    /// Let the bot play until it's the human's turn or the play is over.
+    fn bot_turns(&mut self) -> Result<()> {
+        while let Some(pegging) = self.game.pegging() {
+            if self.game.phase() != Phase::Pegging || pegging.to_play() != Some(BOT) {
+                break;
+            }
+            let state = self
+                .game
+                .pegging_state(BOT)
+                .ok_or_else(|| anyhow!("no play under way"))?;
+            let action = match self.bot.play(&state)? {
+                Some(card) => PegAction::Play(card),
+                None => PegAction::Go,
+            };
+            self.act(BOT, action)?;
+        }
+
+        Ok(())
+    }
+
+    fn next_hand(&mut self) -> Result<()> {
+        self.game.next_hand()?;
+        self.cursor = 0;
+        self.log_deal();
+        Ok(())
+    }
+
+    fn log_deal(&mut self) {
+        self.log.push(format!(
+            "{} {} the deal",
+            name(self.game.dealer()),
+            if self.game.dealer() == HUMAN {
+                "have"
+            } else {
+                "has"
+            }
+        ));
+    }
+
+    fn log_points(&mut self, points: &[Points]) {
+        for Points {
+            player,
+            points,
+            reason,
+        } in points
+        {
+            self.log
+                .push(format!("{}: {reason} for {points}", name(*player)));
+        }
+        if let Some(winner) = self.game.winner() {
+            let skunk = match self.game.board().skunk() {
+                Some(Skunk::Single) => " with a skunk",
+                Some(Skunk::Double) => " with a double skunk",
+                _ => "",
+            };
+            self.log.push(match winner {
+                HUMAN => format!("You win{skunk}!"),
+                _ => format!("The bot wins{skunk}."),
+            });
+        }
+    }
+
+    /// Draw the table as lines of text.
+    pub fn render(&self) -> Vec<String> {
+        let style = self.style;
+        let phase = self.game.phase();
+        let mut lines = Vec::new();
+
+        lines.push("Crible: you vs the bot".to_string());
+        lines.push(String::new());
+        lines.extend(self.game.board().render(&[name(0), name(1)], style));
+        lines.push(String::new());
+
+        let starter = match self.game.starter() {
+            Some(starter) => style.card(starter).to_string(),
+            None => "--".to_string(),
+        };
+        lines.push(format!(
+            "Dealer: {}    Starter: {starter}",
+            name(self.game.dealer())
+        ));
+
+        let revealed = matches!(phase, Phase::Show | Phase::Over) && self.game.starter().is_some();
+        let crib = if revealed {
+            style.cards(self.game.crib())
+        } else {
+            face_down(self.game.crib().len())
+        };
+        lines.push(format!("Crib ({}): {crib}", name(self.game.dealer())));
+        lines.push(String::new());
+
+        let bot_hand = match (phase, self.game.pegging()) {
+            (Phase::Pegging, Some(pegging)) => face_down(pegging.hand(BOT).len()),
+            _ if revealed => style.cards(self.game.hand(BOT)),
+            _ => face_down(self.game.hand(BOT).len()),
+        };
+        lines.push(format!("Bot:  {bot_hand}"));
+
+        if let (Phase::Pegging, Some(pegging)) = (phase, self.game.pegging()) {
+            lines.push(format!(
+                "Count: {: >2}  Pile: {}",
+                pegging.count(),
+                style.cards(pegging.pile())
+            ));
+        } else {
+            lines.push(String::new());
+        }
+
+        let cards = self.human_cards();
+        let hand = cards
+            .iter()
+            .map(|card| {
+                if self.selected.contains(card) {
+                    format!("[{}]", style.card(*card))
+                } else {
+                    format!(" {} ", style.card(*card))
+                }
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!("You: {}", hand.join("")));
+        let cursor = if matches!(phase, Phase::Discard | Phase::Pegging) && !cards.is_empty() {
+            format!("{}^", " ".repeat(5 + 1 + self.cursor * 4))
+        } else {
+            String::new()
+        };
+        lines.push(cursor);
+
+        lines.push(String::new());
+        let skip = self.log.len().saturating_sub(LOG_LINES);
+        lines.extend(self.log[skip..].iter().map(|line| format!("  {line}")));
+        lines.extend((self.log.len() - skip..LOG_LINES).map(|_| String::new()));
+        lines.push(String::new());
+
+        lines.push(match &self.message {
+            Some(message) => message.clone(),
+            None => self.prompt(),
+        });
+
+        lines
+    }
+
+    fn prompt(&self) -> String {
+        match self.game.phase() {
+            Phase::Discard => "←/→ move, space select, enter throw to the crib, q quit".to_string(),
+            Phase::Pegging => match self.game.pegging() {
+                Some(pegging) if !pegging.can_play(HUMAN) => {
+                    "No card to play: enter (or g) to say go, q quit".to_string()
+                }
+                _ => "←/→ move, enter play, q quit".to_string(),
+            },
+            // Games against the bot are played without muggins or auctions
+            Phase::Bid => "Bidding for the crib, q quit".to_string(),
+            Phase::Count => "Counting the hands, q quit".to_string(),
+            Phase::Show => "Hands counted: enter for the next deal, q quit".to_string(),
+            Phase::Over => "Game over: enter or q to leave".to_string(),
+        }
+    }
+}
+
+fn name(player: usize) -> &'static str {
+    match player {
+        HUMAN => "You",
+        _ => "Bot",
+    }
+}
+
+fn face_down(n: usize) -> String {
+    vec!["##"; n].join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table::new(Game::with_dealer(3, BOT), CardStyle::Ascii)
+    }
+
+    #[test]
+    fn renders_deal() {
+        let table = table();
+        let screen = table.render().join("\n");
+
+        assert!(screen.contains("Dealer: Bot"));
+        assert!(screen.contains("Bot:  ## ## ## ## ## ##"));
+        let hand = CardStyle::Ascii.cards(table.game.hand(HUMAN));
+        assert!(screen.contains(&hand.replace(' ', "  ")));
+    }
+
+    #[test]
+    fn discard_then_play_a_hand() {
+        let mut table = table();
+
+        // Enter without 2 cards selected only shows a message
+        assert!(table.handle(Key::Enter));
+        assert_eq!(Phase::Discard, table.game.phase());
+        assert!(table.render().last().unwrap().contains("Select 2 cards"));
+
+        table.handle(Key::Select);
+        table.handle(Key::Right);
+        table.handle(Key::Select);
+        assert!(table.render().join("\n").contains('['));
+        table.handle(Key::Enter);
+        assert_eq!(Phase::Pegging, table.game.phase());
+        assert_eq!(4, table.game.crib().len());
+
+        // The human is pone and leads; play the leftmost card until the hand is counted.
+        while table.game.phase() == Phase::Pegging {
+            assert_eq!(Some(HUMAN), table.game.pegging().unwrap().to_play());
+            table.cursor = 0;
+            table.handle(Key::Enter);
+        }
+        assert!(matches!(table.game.phase(), Phase::Show | Phase::Over));
+
+        let screen = table.render().join("\n");
+        assert!(!screen.contains("##"));
+        assert!(screen.contains("Hands counted") || screen.contains("Game over"));
+    }
+}
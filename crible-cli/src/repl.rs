@@ -0,0 +1,377 @@
+use std::io::{stdin, stdout, BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+use crible_core::*;
+
+use crate::report::{write_ranking, ReportOptions};
+
+const PROMPT: &str = "crible> ";
+
+const HELP: &str = "\
+Commands:
+  <cards>                   best discards for a deal, e.g. 5d 6h Ac 8d Kd Qc
+  score [crib] <hand> <st>  score a hand with its starter (last card), e.g. score 5s 5h 5d Jc 5c
+  bid <cards>               what the crib is worth for a deal, to bid for it in auction cribbage
+  known <cards>             remember cards that can't be the starter
+  forget                    forget every known card
+  history                   list previous results
+  show [n]                  show result n (default: last) in full
+  help                      this message
+  quit                      leave";
+
+enum Entry {
+    Deal {
+        cards: Vec<Card>,
+        known: Vec<Card>,
+        ranking: DiscardRanking,
+    },
+    Score {
+        hand: Vec<Card>,
+        starter: Card,
+        crib: bool,
+        score: u8,
+    },
+}
+
+/// Interactive session: known cards and previous results survive between commands.
+struct Session {
+    style: CardStyle,
+    known: Vec<Card>,
+    history: Vec<Entry>,
+}
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+pub fn run(style: CardStyle) -> Result<()> {
+    let mut session = Session::new(style);
+
+    let mut out = stdout().lock();
+    writeln!(out, "Type `help` for the list of commands.")?;
+
+    let mut lines = stdin().lock().lines();
+    loop {
+        write!(out, "{PROMPT}")?;
+        out.flush()?;
+
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            break;
+        };
+
+        match session.eval(line?.as_str(), &mut out) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => break,
+            Err(err) => match err.downcast_ref::<InputError>() {
+                Some(InputError { position, message }) => {
+                    writeln!(out, "{}^ {message}", " ".repeat(PROMPT.len() + position))?
+                }
+                None => writeln!(out, "error: {err}")?,
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse error located in the whole line typed at the prompt.
+#[derive(Debug)]
+struct InputError {
+    position: usize,
+    message: String,
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl Session {
+    fn new(style: CardStyle) -> Self {
+        Self {
+            style,
+            known: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    fn eval(&mut self, line: &str, out: &mut impl Write) -> Result<Flow> {
+        let line = line.trim_end();
+        let (command, rest) = match line.trim_start().split_once(char::is_whitespace) {
+            Some((command, rest)) => (command, rest),
+            None => (line.trim_start(), ""),
+        };
+        // Where `rest` starts in `line`, to report parse errors at the right column.
+        let offset = line[..line.len() - rest.len()].chars().count();
+
+        match command {
+            "" => {}
+            "help" => writeln!(out, "{HELP}")?,
+            "quit" | "exit" => return Ok(Flow::Quit),
+            "known" => {
+                for card in cards_at(rest, offset)? {
+                    if !self.known.contains(&card) {
+                        self.known.push(card);
+                    }
+                }
+                self.known.sort();
+                writeln!(out, "Known cards: {}", self.style.cards(&self.known))?;
+            }
+            "forget" => {
+                self.known.clear();
+                writeln!(out, "Known cards: none")?;
+            }
+            "score" => self.score(rest, offset, out)?,
+            "bid" => self.bid(rest, offset, out)?,
+            "history" => self.write_history(out)?,
+            "show" => {
+                let n = match rest.trim() {
+                    "" => self.history.len(),
+                    n => n
+                        .parse()
+                        .map_err(|_| anyhow!("invalid result number: {n}"))?,
+                };
+                let entry = n
+                    .checked_sub(1)
+                    .and_then(|idx| self.history.get(idx))
+                    .ok_or_else(|| anyhow!("no result #{n}"))?;
+                self.write_entry(entry, true, out)?;
+            }
+            // Cards are 2 characters, so longer words are commands unless they are cards typed
+            // without spaces
+            _ if command.chars().count() > 2
+                && command.chars().all(char::is_alphabetic)
+                && parse_cards(command).is_err() =>
+            {
+                return Err(InputError {
+                    position: line.chars().count() - line.trim_start().chars().count(),
+                    message: format!("unknown command {command}, type `help` for the list"),
+                }
+                .into())
+            }
+            _ => self.deal(line, out)?,
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn deal(&mut self, line: &str, out: &mut impl Write) -> Result<()> {
+        let mut cards = cards_at(line, 0)?;
+        cards.sort();
+
+        let ranking = DiscardRanking::new(&cards, &self.known)?;
+        self.history.push(Entry::Deal {
+            cards,
+            known: self.known.clone(),
+            ranking,
+        });
+        self.write_entry(self.history.last().unwrap(), false, out)
+    }
+
+    fn score(&mut self, rest: &str, offset: usize, out: &mut impl Write) -> Result<()> {
+        let (crib, rest, offset) = match rest.trim_start().strip_prefix("crib") {
+            Some(cards) => (true, cards, offset + rest.len() - cards.len()),
+            None => (false, rest, offset),
+        };
+
+        let cards = cards_at(rest, offset)?;
+        let Some((starter, hand)) = cards.split_last() else {
+            return Err(anyhow!("expected a hand and a starter"));
+        };
+        let rules = match crib {
+            true => ShowRules::CRIB,
+            false => ShowRules::HAND,
+        };
+        let score = score_cards(hand, *starter, rules)?;
+
+        self.history.push(Entry::Score {
+            hand: hand.to_vec(),
+            starter: *starter,
+            crib,
+            score,
+        });
+        self.write_entry(self.history.last().unwrap(), false, out)
+    }
+
+    fn bid(&self, rest: &str, offset: usize, out: &mut impl Write) -> Result<()> {
+        let mut cards = cards_at(rest, offset)?;
+        cards.sort();
+        let kept = cards.len().saturating_sub(2);
+
+        let mut rng = rand::thread_rng();
+        let worth = crib_worth(&cards, kept, 2000, &mut rng)?;
+        let mut ranking = DiscardRanking::keeping(&cards, &[], kept)?;
+        ranking.rank_with_crib(&cards, true, 2000, &mut rng)?;
+        let best = ranking.best();
+
+        writeln!(
+            out,
+            "Crib worth {worth:.2} points: throw {} to your crib (mean {:.2}), bid up to {}",
+            self.style.cards(&best.discard),
+            best.crib.unwrap_or_default(),
+            worth.floor().max(0.0)
+        )?;
+        Ok(())
+    }
+
+    fn write_history(&self, out: &mut impl Write) -> Result<()> {
+        if self.history.is_empty() {
+            writeln!(out, "No results yet.")?;
+        }
+
+        for (idx, entry) in self.history.iter().enumerate() {
+            write!(out, "{: >3}. ", idx + 1)?;
+            match entry {
+                Entry::Deal { cards, ranking, .. } => {
+                    let best = ranking.best();
+                    writeln!(
+                        out,
+                        "deal {}: keep {} (mean {:.2})",
+                        self.style.cards(cards),
+                        self.style.cards(&best.hand),
+                        best.scores.mean()
+                    )?
+                }
+                Entry::Score { .. } => self.write_entry(entry, false, out)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Print an entry; `full` lists every keep of a deal instead of the best few.
+    fn write_entry(&self, entry: &Entry, full: bool, out: &mut impl Write) -> Result<()> {
+        match entry {
+            Entry::Deal {
+                cards,
+                known,
+                ranking,
+            } => {
+                writeln!(out, "Deal: {}", self.style.cards(cards))?;
+                if !known.is_empty() {
+                    writeln!(out, "Known cards: {}", self.style.cards(known))?;
+                }
+                writeln!(out)?;
+
+                let mut opts = ReportOptions {
+                    style: self.style,
+                    ..Default::default()
+                };
+                if full {
+                    opts.top_n = None;
+                }
+                write_ranking(out, ranking, &opts)?;
+            }
+            Entry::Score {
+                hand,
+                starter,
+                crib,
+                score,
+            } => writeln!(
+                out,
+                "{} {} with {}: {score} points",
+                if *crib { "Crib" } else { "Hand" },
+                self.style.cards(hand),
+                self.style.card(*starter)
+            )?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse cards found `offset` chars into the line, so errors point at the right column.
+fn cards_at(input: &str, offset: usize) -> Result<Vec<Card>> {
+    parse_cards(input).map_err(|err| match err.downcast_ref::<ParseError>() {
+        Some(parse_error) => InputError {
+            position: offset + parse_error.position,
+            message: parse_error.to_string(),
+        }
+        .into(),
+        None => err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `line` typed in `session`.
+    fn eval(session: &mut Session, line: &str) -> Result<String> {
+        let mut out = Vec::new();
+        session.eval(line, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn scores_hands() -> Result<()> {
+        let mut session = Session::new(CardStyle::Ascii);
+        assert_eq!(
+            "Hand 5s 5h 5d Jc with 5c: 29 points\n",
+            eval(&mut session, "score 5s 5h 5d Jc 5c")?
+        );
+        // A flush of the crib only counts with the starter
+        assert_eq!(
+            "Crib 2h 4h 6h 8h with Kc: 0 points\n",
+            eval(&mut session, "score crib 2h 4h 6h 8h Kc")?
+        );
+        assert!(eval(&mut session, "score").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn remembers_known_cards() -> Result<()> {
+        let mut session = Session::new(CardStyle::Ascii);
+        assert_eq!("Known cards: 5c Jh\n", eval(&mut session, "known 5c Jh")?);
+        assert!(eval(&mut session, "5d 5h 5s Jc 2h 9s")?.contains("Known cards: 5c Jh"));
+
+        assert_eq!("Known cards: none\n", eval(&mut session, "forget")?);
+        assert!(!eval(&mut session, "5d 5h 5s Jc 2h 9s")?.contains("Known cards"));
+        Ok(())
+    }
+
+    #[test]
+    fn lists_and_shows_results() -> Result<()> {
+        let mut session = Session::new(CardStyle::Ascii);
+        assert_eq!("No results yet.\n", eval(&mut session, "history")?);
+        assert!(eval(&mut session, "show").is_err());
+
+        eval(&mut session, "5d 5h 5s Jc 2h 9s")?;
+        eval(&mut session, "score 5s 5h 5d Jc 5c")?;
+        let history = eval(&mut session, "history")?;
+        assert!(history.starts_with("  1. deal "), "{history}");
+        assert!(history.contains("  2. Hand 5s 5h 5d Jc with 5c: 29 points"));
+
+        assert_eq!(
+            "Hand 5s 5h 5d Jc with 5c: 29 points\n",
+            eval(&mut session, "show")?
+        );
+        let deal = eval(&mut session, "show 1")?;
+        assert!(deal.starts_with("Deal: "));
+        // In full, every keep is listed
+        assert!(deal.lines().count() > eval(&mut session, "5d 5h 5s Jc 2h 9s")?.lines().count());
+        assert!(eval(&mut session, "show 9").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reports_unknown_commands() {
+        let mut session = Session::new(CardStyle::Ascii);
+        let err = eval(&mut session, "  scroe 5h 5d").unwrap_err();
+        let err = err.downcast_ref::<InputError>().unwrap();
+        assert_eq!(2, err.position);
+        assert!(err.message.starts_with("unknown command scroe"));
+
+        // Cards typed without spaces are still a deal, and typos in cards point at the card
+        assert!(eval(&mut session, "5d5h5sJc2h9s").is_ok());
+        let err = eval(&mut session, "5d 5h 5s Jx 2h 9s").unwrap_err();
+        assert_eq!(10, err.downcast_ref::<InputError>().unwrap().position);
+        assert_eq!(1, session.history.len());
+    }
+}
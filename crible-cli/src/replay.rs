@@ -0,0 +1,163 @@
+use std::io::{stdout, Write};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crible_core::{replay, CardStyle, Event, GameRecord, PegAction};
+
+/// Step through the game saved at `path`, checking every score against the engine's.
+pub fn run(path: &str, style: CardStyle) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|err| anyhow!("can't read {path}: {err}"))?;
+    let record: GameRecord = text.parse()?;
+    write_replay(&mut stdout().lock(), &record, style)
+}
+
+/// Describe each event of `record` to `out`, then the final scores once they all check out.
+fn write_replay(out: &mut impl Write, record: &GameRecord, style: CardStyle) -> Result<()> {
+    let names = record.player_names();
+    let mut lines = Vec::new();
+    let variant = record.variant;
+    // Below 0 while a bid past the score is owed
+    let mut scores = vec![0i32; variant.teams()];
+    let game = replay(record, |event, game| {
+        match event {
+            Event::Points(points) => {
+                let score = &mut scores[variant.team(points.player)];
+                *score = (*score + points.points as i32).min(record.rules.game as i32);
+            }
+            // Nothing is left to score when the auction ends: the board has the bid paid
+            Event::Bid { bid: None, .. } => {
+                for (team, score) in scores.iter_mut().enumerate() {
+                    let board = game.board();
+                    *score = board.score(team) as i32 - board.debt(team) as i32;
+                }
+            }
+            _ => {}
+        }
+        lines.push(describe(event, &names, style, &scores));
+    });
+    // Show how far the game went before a score didn't check out
+    for line in lines {
+        writeln!(out, "{line}")?;
+    }
+    let game = game?;
+
+    let scores = record
+        .team_names()
+        .iter()
+        .zip(game.scores())
+        .map(|(name, score)| format!("{name} {score}"))
+        .join(", ");
+    writeln!(out, "\nEvery score checks out: {scores}.")?;
+    Ok(())
+}
+
+fn describe(event: &Event, names: &[String], style: CardStyle, scores: &[i32]) -> String {
+    match event {
+        Event::Hand { dealer } => format!("\n{} deals.", names[*dealer]),
+        Event::Deal { player, cards } => {
+            format!("{} is dealt {}", names[*player], style.cards(cards))
+        }
+        Event::Bid {
+            player,
+            bid: Some(bid),
+        } => format!("{} bids {bid} for the crib", names[*player]),
+        Event::Bid { player, bid: None } => {
+            format!("{} passes ({})", names[*player], scores.iter().join("-"))
+        }
+        Event::Discard { player, cards } => {
+            format!("{} throws {}", names[*player], style.cards(cards))
+        }
+        Event::CribCard(card) => format!("{} goes to the crib", style.card(*card)),
+        Event::Starter(card) => format!("Starter: {}", style.card(*card)),
+        Event::Peg {
+            player,
+            action: PegAction::Play(card),
+        } => format!("{} plays {}", names[*player], style.card(*card)),
+        Event::Peg {
+            player,
+            action: PegAction::Go,
+        } => format!("{} says go", names[*player]),
+        Event::Count { player, points } => format!("{} counts {points}", names[*player]),
+        Event::Points(points) => format!(
+            "  {} scores {} for {} ({})",
+            names[points.player],
+            points.points,
+            points.reason,
+            scores.iter().join("-")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crible_core::*;
+
+    use super::*;
+
+    #[test]
+    fn replays_an_auction() -> Result<()> {
+        let mut game = Game::with_variant(Variant::Auction, 3, 0);
+        game.bid(1, Some(3))?;
+        game.bid(0, Some(5))?;
+        game.bid(1, None)?;
+        play_game(&mut game, &[&GreedyShow, &GreedyShow])?;
+
+        let mut out = Vec::new();
+        write_replay(&mut out, game.record(), CardStyle::Ascii)?;
+        let out = String::from_utf8(out)?;
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(["", "Player 0 deals."], lines[..2], "{out}");
+        assert!(lines[3].starts_with("Player 1 is dealt "), "{out}");
+        assert_eq!(
+            [
+                "Player 1 bids 3 for the crib",
+                "Player 0 bids 5 for the crib",
+                // Owed until Player 0 scores 5
+                "Player 1 passes (-5-0)",
+            ],
+            lines[4..7]
+        );
+        let scores = game.scores();
+        assert_eq!(
+            Some(
+                &format!(
+                    "Every score checks out: Player 0 {}, Player 1 {}.",
+                    scores[0], scores[1]
+                )
+                .as_str()
+            ),
+            lines.last()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn describes_events() -> Result<()> {
+        let names = ["Ann".to_string(), "Bob".to_string()];
+        let describe = |event| describe(&event, &names, CardStyle::Ascii, &[-2, 7]);
+        assert_eq!("\nBob deals.", describe(Event::Hand { dealer: 1 }));
+        assert_eq!(
+            "Ann throws 5h Jc",
+            describe(Event::Discard {
+                player: 0,
+                cards: parse_cards("5h Jc")?,
+            })
+        );
+        assert_eq!(
+            "Bob passes (-2-7)",
+            describe(Event::Bid {
+                player: 1,
+                bid: None
+            })
+        );
+        assert_eq!(
+            "Ann says go",
+            describe(Event::Peg {
+                player: 0,
+                action: PegAction::Go,
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::Write;
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use crible_core::*;
+
+/// How much of a `DiscardRanking` to print.
+pub struct ReportOptions {
+    /// Number of keeps to show, `None` to show them all.
+    pub top_n: Option<usize>,
+    /// Number of starters to list per score, `None` to list them all.
+    pub max_starters: Option<usize>,
+    /// List starters as "any 5 (4)" instead of one card at a time.
+    pub by_rank: bool,
+    pub style: CardStyle,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            top_n: Some(4),
+            max_starters: Some(10),
+            by_rank: false,
+            style: CardStyle::default(),
+        }
+    }
+}
+
+pub fn write_ranking(
+    w: &mut impl Write,
+    ranking: &DiscardRanking,
+    opts: &ReportOptions,
+) -> Result<()> {
+    let top_n = opts.top_n.unwrap_or(ranking.len());
+
+    for Keep {
+        hand,
+        scores,
+        win,
+        crib,
+        ..
+    } in ranking.iter().take(top_n)
+    {
+        let mut top_starters: Vec<(u8, Vec<Card>)> = Default::default();
+        for (score, chunks) in &scores.iter().chunk_by(|(_, score)| *score) {
+            let mut starters = chunks.map(|(card, _)| card).collect::<Vec<_>>();
+            starters.sort();
+            top_starters.push((score, starters));
+        }
+
+        write!(
+            w,
+            "Hand: {}  Mean: {:.2}",
+            opts.style.cards(hand),
+            scores.mean()
+        )?;
+        if let Some(crib) = crib {
+            write!(w, "  Crib: {crib:+.2}")?;
+        }
+        match win {
+            Some(win) => writeln!(w, "  Win: {:.1}%", 100.0 * win)?,
+            None => writeln!(w)?,
+        }
+        writeln!(w, "  Top starters: ")?;
+        for (score, starters) in top_starters {
+            write!(w, "      {: >2} points: ", score)?;
+
+            let (entries, separator) = if opts.by_rank {
+                (group_by_rank(&starters, opts.style), ", ")
+            } else {
+                let styled = starters.iter().map(|card| opts.style.card(*card));
+                (styled.map(|card| card.to_string()).collect(), " ")
+            };
+            let max = opts.max_starters.unwrap_or(entries.len());
+            write!(w, "{}", entries.iter().take(max).join(separator))?;
+            if entries.len() > max {
+                write!(w, " ...")?;
+            }
+            writeln!(w)?;
+        }
+        writeln!(w)?;
+    }
+
+    match ranking.len().saturating_sub(top_n) {
+        0 => {} // no-op
+        1 => writeln!(w, "... and 1 worse hand")?,
+        n => writeln!(w, "... and {n} worse hands")?,
+    }
+
+    Ok(())
+}
+
+/// Collapse sorted starters of the same number into a single entry, e.g. "any 5 (4)".
+fn group_by_rank(starters: &[Card], style: CardStyle) -> Vec<String> {
+    starters
+        .iter()
+        .chunk_by(|card| (!card.is_joker()).then(|| card.number()))
+        .into_iter()
+        .map(|(number, cards)| {
+            let cards = cards.collect::<Vec<_>>();
+            match number {
+                _ if cards.len() == 1 => style.card(*cards[0]).to_string(),
+                Some(number) => format!("any {number} ({})", cards.len()),
+                None => format!("any joker ({})", cards.len()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(opts: ReportOptions) -> Result<String> {
+        let ranking = DiscardRanking::new(&parse_cards("5d 5h 5s Jc 2h 9s")?, &[])?;
+        let mut out = Vec::new();
+        write_ranking(
+            &mut out,
+            &ranking,
+            &ReportOptions {
+                style: CardStyle::Ascii,
+                ..opts
+            },
+        )?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn shows_the_top_hands() -> Result<()> {
+        let out = report(ReportOptions {
+            top_n: Some(2),
+            ..Default::default()
+        })?;
+        assert!(out.starts_with("Hand: 5d 5h 5s Jc  Mean: 16.65\n"), "{out}");
+        assert_eq!(2, out.matches("Hand: ").count());
+        assert!(out.ends_with("\n... and 13 worse hands\n"));
+
+        let out = report(ReportOptions {
+            top_n: Some(14),
+            ..Default::default()
+        })?;
+        assert!(out.ends_with("\n... and 1 worse hand\n"));
+
+        let out = report(ReportOptions {
+            top_n: None,
+            ..Default::default()
+        })?;
+        assert_eq!(15, out.matches("Hand: ").count());
+        assert!(!out.contains("worse"));
+        Ok(())
+    }
+
+    #[test]
+    fn truncates_starters() -> Result<()> {
+        let top = |max_starters| {
+            report(ReportOptions {
+                top_n: Some(1),
+                max_starters,
+                ..Default::default()
+            })
+        };
+        let fourteen = |out: &str| {
+            let line = out
+                .lines()
+                .find(|line| line.contains("14 points: "))
+                .unwrap();
+            line.to_string()
+        };
+
+        let out = top(Some(10))?;
+        assert!(out.contains("      29 points: 5c\n"));
+        let line = fourteen(&out);
+        assert!(line.ends_with(" ..."), "{line}");
+        assert_eq!(10 + 1, line.split(": ").nth(1).unwrap().split(' ').count());
+
+        let all = fourteen(&top(None)?);
+        assert!(!all.contains("..."));
+        assert_eq!(22, all.split(": ").nth(1).unwrap().split(' ').count());
+        Ok(())
+    }
+
+    #[test]
+    fn groups_starters_by_rank() -> Result<()> {
+        let out = report(ReportOptions {
+            top_n: Some(1),
+            by_rank: true,
+            ..Default::default()
+        })?;
+        assert!(out.contains("      22 points: any J (3)\n"), "{out}");
+        assert!(out.contains("      21 points: Tc, Qc, Kc\n"));
+
+        let mut starters = parse_cards("9s 5h 5d 5s 5c")?;
+        starters.sort();
+        assert_eq!(
+            vec!["any 5 (4)", "9s"],
+            group_by_rank(&starters, CardStyle::Ascii)
+        );
+        assert_eq!(
+            vec!["any joker (2)"],
+            group_by_rank(&Card::JOKERS, CardStyle::Ascii)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{stdin, stdout, BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+use crible_core::*;
+
+/// A game of cribbage squares on the terminal, the advisor's pick suggested for every card.
+pub fn run(style: CardStyle, seed: u64, depth: usize) -> Result<()> {
+    let mut squares = Squares::new(seed);
+    let mut out = stdout().lock();
+    let mut lines = stdin().lock().lines();
+
+    writeln!(out, "Starter: {}", style.card(squares.starter()))?;
+    while let Some(card) = squares.card() {
+        writeln!(out, "\n{}", render_grid(squares.grid(), style))?;
+        let best = squares.advise(depth)?[0];
+        write!(
+            out,
+            "Place {} in square 1-{SQUARES} (enter for {}, worth {:.1}): ",
+            style.card(card),
+            best.square + 1,
+            best.value
+        )?;
+        out.flush()?;
+
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(());
+        };
+        let line = line?;
+        let square = match line.trim() {
+            "" => best.square,
+            n => match n.parse::<usize>() {
+                Ok(n @ 1..=SQUARES) => n - 1,
+                _ => {
+                    writeln!(out, "error: no square {n}")?;
+                    continue;
+                }
+            },
+        };
+        if let Err(err) = squares.place(square) {
+            writeln!(out, "error: {err}")?;
+        }
+    }
+
+    writeln!(out, "\n{}", render_grid(squares.grid(), style))?;
+    let score = squares
+        .score()
+        .ok_or_else(|| anyhow!("the grid isn't full"))?;
+    writeln!(out, "Score: {score}")?;
+    Ok(())
+}
+
+/// The grid row by row, empty squares showing their number.
+fn render_grid(grid: &Grid, style: CardStyle) -> String {
+    (0..4)
+        .map(|row| {
+            (row * 4..row * 4 + 4)
+                .map(|square| match grid.get(square) {
+                    Some(card) => style.card(card).to_string(),
+                    None => format!("{:>2}", square + 1),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,95 @@
+use std::io::{stderr, stdout, IsTerminal, Write};
+
+use anyhow::Result;
+
+use crible_core::{Tournament, TournamentResult};
+
+/// Play the tournament, showing progress on a terminal, and print the standings.
+pub fn run(tournament: &Tournament, total: u32) -> Result<()> {
+    let show_progress = stderr().is_terminal();
+    let result = tournament.run(|played| {
+        if show_progress && (played % 10 == 0 || played == total) {
+            eprint!("\rPlayed {played}/{total} games");
+        }
+    })?;
+    if show_progress {
+        eprintln!();
+    }
+
+    write_result(&mut stdout().lock(), &result)
+}
+
+pub fn write_result(w: &mut impl Write, result: &TournamentResult) -> Result<()> {
+    let width = result
+        .standings
+        .iter()
+        .map(|standing| standing.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Player".len());
+
+    writeln!(
+        w,
+        "{:width$}  {:>5}  {:>6}  {:>13}  {:>5}  {:>5}  {:>5}  {:>5}  {:>6}  {:>7}",
+        "Player", "Games", "Win %", "95% CI", "Elo", "Peg", "Hand", "Crib", "Skunks", "Skunked"
+    )?;
+
+    let mut order = (0..result.standings.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        result.standings[*b]
+            .elo
+            .total_cmp(&result.standings[*a].elo)
+    });
+
+    for standing in order.iter().map(|n| &result.standings[*n]) {
+        let (low, high) = standing.win_interval();
+        let (pegging, hand, crib) = standing.points_per_game();
+        writeln!(
+            w,
+            "{:width$}  {:>5}  {:>5.1}%  {:>13}  {:>5.0}  {:>5.1}  {:>5.1}  {:>5.1}  {:>5.1}%  {:>6.1}%",
+            standing.name,
+            standing.games,
+            100.0 * standing.win_rate(),
+            format!("{:.1}-{:.1}%", 100.0 * low, 100.0 * high),
+            standing.elo,
+            pegging,
+            hand,
+            crib,
+            100.0 * standing.skunk_rate(),
+            100.0 * standing.skunked_rate(),
+        )?;
+    }
+
+    writeln!(w, "\nPoints are averages per game. Head to head:")?;
+    for pairing in &result.pairings {
+        writeln!(
+            w,
+            "  {} {}-{} {}",
+            result.standings[pairing.a].name,
+            pairing.a_wins,
+            pairing.games - pairing.a_wins,
+            result.standings[pairing.b].name,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_standings() -> Result<()> {
+        let result = Tournament::new(&["random", "bot"], 4, 1)?.run(|_| {})?;
+        let mut out = Vec::new();
+        write_result(&mut out, &result)?;
+        let out = String::from_utf8(out)?;
+
+        assert!(out.starts_with("Player"));
+        assert!(out.contains("  random "));
+        let wins = result.pairings[0].a_wins;
+        assert!(out.contains(&format!("random {}-{} bot", wins, 4 - wins)));
+        Ok(())
+    }
+}
@@ -0,0 +1,457 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    greedy_play, replay, Card, CardStyle, DiscardRanking, Event, GameRecord, Objective, PegAction,
+    Pegging, PeggingState, RuleSet, Scores, Variant,
+};
+
+/// A decision taken during a game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Choice {
+    Discard(Vec<Card>),
+    Peg(PegAction),
+}
+
+impl Display for Choice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Choice::Discard(cards) => write!(f, "throw {}", CardStyle::Ascii.cards(cards)),
+            Choice::Peg(PegAction::Play(card)) => {
+                write!(f, "play {}", CardStyle::Ascii.card(*card))
+            }
+            Choice::Peg(PegAction::Go) => write!(f, "go"),
+        }
+    }
+}
+
+/// A decision compared with the best one, in expected points, counted against the player in
+/// lowball.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Decision {
+    /// Hand of the game the decision was taken in, from 1.
+    pub hand: usize,
+    pub player: usize,
+    pub actual: Choice,
+    pub best: Choice,
+    pub actual_value: f32,
+    pub best_value: f32,
+}
+
+impl Decision {
+    /// Expected points lost by not taking the best choice.
+    pub fn loss(&self) -> f32 {
+        (self.best_value - self.actual_value).max(0.0)
+    }
+}
+
+/// Every decision of a game where players had a choice, with the expected points it lost.
+///
+/// Discards are valued by the mean of the hand kept over every starter, plus the mean of the
+/// crib when it's the player's team's, minus it otherwise. Plays are valued by the points pegged
+/// by the player's team for the rest of the play minus the opponents', averaged over guesses of
+/// the cards the others hold and finished greedily by everyone, under the record's rules. In
+/// lowball, the fewest points are best. Estimates are sampled from the game's seed, so an
+/// analysis is the same every time.
+pub struct Analysis {
+    pub decisions: Vec<Decision>,
+}
+
+impl Analysis {
+    /// Analyze the game of `record`, with `samples` random draws for each estimate.
+    pub fn new(record: &GameRecord, samples: u32) -> Result<Self> {
+        let mut rng = StdRng::seed_from_u64(record.seed);
+        let mut decisions = Vec::new();
+
+        let mut hand = 0;
+        let mut dealer = 0;
+        let players = record.variant.players();
+        let mut dealt = vec![Vec::new(); players];
+        let mut discards = vec![Vec::new(); players];
+        let mut starter = None;
+        let mut pegging: Option<Pegging> = None;
+        let mut scores = vec![Scores::default(); players];
+        let mut error = None;
+
+        replay(record, |event, game| {
+            match event {
+                Event::Hand { dealer: d } => {
+                    hand += 1;
+                    dealer = *d;
+                    discards.iter_mut().for_each(Vec::clear);
+                    pegging = None;
+                }
+                Event::Deal { player, cards } => dealt[*player] = cards.clone(),
+                Event::Discard { player, cards } => {
+                    discards[*player] = cards.clone();
+                    let variant = record.variant;
+                    let decision = discard_decision(
+                        &dealt[*player],
+                        cards,
+                        variant.kept(),
+                        variant.objective(),
+                        variant.team(*player) == variant.team(game.crib_owner()),
+                        samples,
+                        &mut rng,
+                    );
+                    match decision {
+                        Ok(decision) => decisions.push(Decision {
+                            hand,
+                            player: *player,
+                            ..decision
+                        }),
+                        Err(err) => {
+                            error.get_or_insert(err);
+                        }
+                    }
+                }
+                Event::Starter(card) => {
+                    starter = Some(*card);
+                    pegging = game.pegging().cloned();
+                }
+                Event::Peg { player, action } => {
+                    let (Some(before), Some(starter)) = (pegging.as_mut(), starter) else {
+                        error.get_or_insert(anyhow!("play before the starter"));
+                        return;
+                    };
+                    let state = PeggingState::new(
+                        *player,
+                        before,
+                        starter,
+                        &discards[*player],
+                        *player == dealer,
+                        scores[*player],
+                    )
+                    .with_rules(record.rules);
+                    let decision = peg_decision(
+                        &state,
+                        *action,
+                        record.variant,
+                        &record.rules,
+                        samples,
+                        &mut rng,
+                    );
+                    if let Some(decision) = decision {
+                        decisions.push(Decision {
+                            hand,
+                            player: *player,
+                            ..decision
+                        });
+                    }
+                    if let Err(err) = before.act(*player, *action) {
+                        error.get_or_insert(err);
+                    }
+                }
+                Event::Bid { .. } | Event::CribCard(_) | Event::Count { .. } | Event::Points(_) => {
+                }
+            }
+            scores = (0..players).map(|player| game.scores_for(player)).collect();
+        })?;
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(Self { decisions }),
+        }
+    }
+
+    /// Expected points `player` lost over the game.
+    pub fn loss(&self, player: usize) -> f32 {
+        self.decisions
+            .iter()
+            .filter(|decision| decision.player == player)
+            .map(Decision::loss)
+            .sum()
+    }
+}
+
+fn discard_decision(
+    dealt: &[Card],
+    actual: &[Card],
+    kept: usize,
+    objective: Objective,
+    owns_crib: bool,
+    samples: u32,
+    rng: &mut impl Rng,
+) -> Result<Decision> {
+    let mut ranking = DiscardRanking::keeping(dealt, &[], kept)?.with_objective(objective);
+    ranking.rank_with_crib(dealt, owns_crib, samples, rng)?;
+    let sign = objective.sign() as f32;
+
+    let actual_value = ranking
+        .iter()
+        .find(|keep| {
+            keep.discard.len() == actual.len()
+                && keep.discard.iter().all(|card| actual.contains(card))
+        })
+        .map(|keep| sign * keep.value())
+        .ok_or_else(|| anyhow!("discard not dealt"))?;
+    let best = ranking.best();
+
+    Ok(Decision {
+        hand: 0,
+        player: 0,
+        actual: Choice::Discard(actual.to_vec()),
+        best: Choice::Discard(best.discard.clone()),
+        actual_value,
+        best_value: sign * best.value(),
+    })
+}
+
+/// Value of every play open to the player of `state`, or `None` when the play is forced.
+fn peg_decision(
+    state: &PeggingState,
+    actual: PegAction,
+    variant: Variant,
+    rules: &RuleSet,
+    samples: u32,
+    rng: &mut impl Rng,
+) -> Option<Decision> {
+    let actions = state.playable().map(PegAction::Play).collect::<Vec<_>>();
+    if actions.len() < 2 {
+        return None;
+    }
+
+    let mut totals = vec![0i32; actions.len()];
+    for _ in 0..samples {
+        // The same guess for every action, so they are compared on the same cards
+        let guess = state.determinize(rng);
+        for (action, total) in actions.iter().zip(totals.iter_mut()) {
+            *total +=
+                rollout(guess.clone(), state, *action, variant, rules) * variant.objective().sign();
+        }
+    }
+
+    let values = totals
+        .iter()
+        .map(|total| *total as f32 / samples.max(1) as f32)
+        .collect::<Vec<_>>();
+    let best = (0..actions.len())
+        .max_by(|a, b| values[*a].total_cmp(&values[*b]))
+        .expect("2 actions or more");
+    let actual_value = actions
+        .iter()
+        .position(|action| *action == actual)
+        .map(|n| values[n])?;
+
+    Some(Decision {
+        hand: 0,
+        player: 0,
+        actual: Choice::Peg(actual),
+        best: Choice::Peg(actions[best]),
+        actual_value,
+        best_value: values[best],
+    })
+}
+
+/// Points pegged by the team of the player of `state` after taking `action` minus the opponents',
+/// everyone playing greedily.
+fn rollout(
+    mut pegging: Pegging,
+    state: &PeggingState,
+    action: PegAction,
+    variant: Variant,
+    rules: &RuleSet,
+) -> i32 {
+    let team = variant.team(state.player());
+    let me = state.player();
+    let mut net = 0;
+    let mut next = Some((me, action));
+
+    while let Some((player, action)) = next {
+        let pegs = pegging.act(player, action).expect("legal action");
+        for (scorer, peg) in pegs {
+            let points = rules.peg_points(peg) as i32;
+            net += if variant.team(scorer) == team {
+                points
+            } else {
+                -points
+            };
+        }
+
+        next = pegging.to_play().map(|player| {
+            let view = PeggingState::new(
+                player,
+                &pegging,
+                state.starter(),
+                &[],
+                false,
+                Scores::default(),
+            )
+            .with_objective(variant.objective())
+            .with_rules(*rules);
+            match greedy_play(&view) {
+                Some(card) => (player, PegAction::Play(card)),
+                None => (player, PegAction::Go),
+            }
+        });
+    }
+
+    net
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_cards, play_game, Bot, Game, RandomPlayer, Variant};
+
+    #[test]
+    fn discard_loss() -> Result<()> {
+        use Objective::{Fewest, Most};
+
+        let dealt = parse_cards("5d 5h 5s Jc 2h 9s")?;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let good = discard_decision(
+            &dealt,
+            &parse_cards("2h 9s")?,
+            4,
+            Most,
+            false,
+            100,
+            &mut rng,
+        )?;
+        assert_eq!(0.0, good.loss());
+        assert_eq!(good.actual, good.best);
+
+        let bad = discard_decision(
+            &dealt,
+            &parse_cards("5d 5h")?,
+            4,
+            Most,
+            false,
+            100,
+            &mut rng,
+        )?;
+        assert!(bad.loss() > 10.0, "{}", bad.loss());
+
+        // In lowball, the 5s are better thrown away
+        let lowball = discard_decision(
+            &dealt,
+            &parse_cards("2h 9s")?,
+            4,
+            Fewest,
+            false,
+            100,
+            &mut rng,
+        )?;
+        assert!(lowball.loss() > 10.0, "{}", lowball.loss());
+        assert!(lowball.actual_value < 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_game_played_over_the_board() -> Result<()> {
+        let record: GameRecord = "\
+            [Dealer \"0\"]
+            [Player0 \"Ann\"]
+            dealer 0
+            deal 0 2h 5h 5d 5s 9s Jc
+            deal 1 Ac 3c 6h 7h 8s Qd
+            discard 0 Jc 9s
+            discard 1 3c Qd
+            starter 5c
+            play 1 8s
+            play 0 5d
+            play 1 7h
+            play 0 2h
+            play 1 6h
+            go 0
+            play 1 Ac
+            points 1 1 go
+            play 0 5h
+            play 0 5s
+            points 0 2 pair
+            points 0 1 last card
+            points 1 8 hand
+            points 0 20 hand
+            points 0 5 crib"
+            .parse()?;
+
+        let analysis = Analysis::new(&record, 50)?;
+        let discard = analysis
+            .decisions
+            .iter()
+            .find(|decision| decision.player == 0 && matches!(decision.actual, Choice::Discard(_)))
+            .expect("Ann's discard");
+        assert_eq!(Choice::Discard(parse_cards("2h 9s")?), discard.best);
+        assert!(discard.loss() > 2.0, "{}", discard.loss());
+        assert!(analysis
+            .decisions
+            .iter()
+            .any(|decision| decision.player == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn peg_loss() {
+        let hands = vec![parse_cards("Th").unwrap(), parse_cards("5s 9d Kc").unwrap()];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+        pegging
+            .act(0, PegAction::Play(parse_cards("Th").unwrap()[0]))
+            .unwrap();
+        let starter = parse_cards("Ac").unwrap()[0];
+        let state = PeggingState::new(1, &pegging, starter, &[], false, Scores::default());
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let nine = PegAction::Play(parse_cards("9d").unwrap()[0]);
+        let rules = RuleSet::standard();
+        let decision = peg_decision(&state, nine, Variant::Standard, &rules, 20, &mut rng).unwrap();
+        assert_eq!(
+            Choice::Peg(PegAction::Play(parse_cards("5s").unwrap()[0])),
+            decision.best
+        );
+        assert!(decision.loss() >= 2.0);
+    }
+
+    #[test]
+    fn analyze_game() -> Result<()> {
+        let random = RandomPlayer::new(1);
+        let mut game = Game::new(4);
+        play_game(&mut game, &[&Bot, &random])?;
+
+        let analysis = Analysis::new(game.record(), 20)?;
+        assert!(analysis
+            .decisions
+            .iter()
+            .any(|decision| matches!(decision.actual, Choice::Discard(_))));
+        assert!(analysis
+            .decisions
+            .iter()
+            .any(|decision| matches!(decision.actual, Choice::Peg(_))));
+        assert!(analysis.loss(1) > analysis.loss(0));
+        Ok(())
+    }
+
+    #[test]
+    fn partner_crib_counts_for_the_team() -> Result<()> {
+        let dealt = parse_cards("5d 5h Ks Qc 3h")?;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let five = parse_cards("5d")?;
+        let partner = discard_decision(&dealt, &five, 4, Objective::Most, true, 200, &mut rng)?;
+        let opponent = discard_decision(&dealt, &five, 4, Objective::Most, false, 200, &mut rng)?;
+        assert!(partner.actual_value > opponent.actual_value + 4.0);
+        assert!(opponent.loss() > partner.loss());
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_three_player_game() -> Result<()> {
+        let random = RandomPlayer::new(1);
+        let mut game = Game::new_variant(Variant::ThreePlayer, 6);
+        play_game(&mut game, &[&Bot, &random, &Bot])?;
+
+        let analysis = Analysis::new(game.record(), 10)?;
+        assert!(analysis
+            .decisions
+            .iter()
+            .any(|decision| decision.player == 2));
+        assert!(analysis.decisions.iter().all(
+            |decision| !matches!(&decision.actual, Choice::Discard(cards) if cards.len() != 1)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,424 @@
+use anyhow::{anyhow, Result};
+
+use crate::{CardStyle, Objective, GAME_POINTS};
+
+/// Losing a game to 121 with fewer points than this is a skunk.
+pub const SKUNK_LINE: u8 = 91;
+/// Losing a game to 121 with fewer points than this is a double skunk.
+pub const DOUBLE_SKUNK_LINE: u8 = 61;
+
+/// Holes in each street of the board.
+const STREET: usize = 60;
+
+/// How badly the loser of a game was beaten.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Skunk {
+    /// The loser passed the skunk line.
+    None,
+    /// The loser finished below the skunk line.
+    Single,
+    /// The loser finished below the double skunk line.
+    Double,
+}
+
+impl Skunk {
+    /// Skunk for a loser finishing on `score` in a game to `target`.
+    ///
+    /// The lines sit as far from the end as in a game to 121: a game to 61 is skunked below 31,
+    /// one to 181 below 151.
+    pub fn of(score: u8, target: u8) -> Self {
+        let short = target.saturating_sub(score);
+        if short > GAME_POINTS - DOUBLE_SKUNK_LINE {
+            Skunk::Double
+        } else if short > GAME_POINTS - SKUNK_LINE {
+            Skunk::Single
+        } else {
+            Skunk::None
+        }
+    }
+
+    /// Game points the winner takes in match play: 1 for a win, 2 for a skunk, 3 for a double.
+    pub fn game_points(self) -> u8 {
+        match self {
+            Skunk::None => 1,
+            Skunk::Single => 2,
+            Skunk::Double => 3,
+        }
+    }
+}
+
+/// A cribbage board, two pegs per player.
+///
+/// Points are pegged by leapfrogging: the back peg jumps ahead of the front one, which becomes
+/// the back peg. The front peg is the score, the gap between the two the last points pegged.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Board {
+    /// Front peg of each player, their score.
+    fronts: Vec<u8>,
+    /// Back peg of each player, 0 until they pegged twice.
+    backs: Vec<u8>,
+    /// Points each player paid without having them, to pay off before moving their pegs again.
+    debts: Vec<u8>,
+    /// Points needed to win, or to lose in lowball.
+    target: u8,
+    objective: Objective,
+}
+
+impl Board {
+    /// A board for a game to `GAME_POINTS`.
+    pub fn new(players: usize) -> Self {
+        Self::with_target(players, GAME_POINTS)
+    }
+
+    /// A board for a game to `target`.
+    pub fn with_target(players: usize, target: u8) -> Self {
+        Self {
+            fronts: vec![0; players],
+            backs: vec![0; players],
+            debts: vec![0; players],
+            target,
+            objective: Objective::Most,
+        }
+    }
+
+    /// The same board where reaching the target wins, or loses in lowball.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn players(&self) -> usize {
+        self.fronts.len()
+    }
+
+    /// Points ending the game.
+    pub fn target(&self) -> u8 {
+        self.target
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Score of every player.
+    pub fn scores(&self) -> &[u8] {
+        &self.fronts
+    }
+
+    pub fn score(&self, player: usize) -> u8 {
+        self.fronts[player]
+    }
+
+    /// Front and back pegs of `player`.
+    pub fn pegs(&self, player: usize) -> (u8, u8) {
+        (self.fronts[player], self.backs[player])
+    }
+
+    /// Points `player` owes, paid for a crib beyond their score. Their pegs stay in the first hole
+    /// until they score as much.
+    pub fn debt(&self, player: usize) -> u8 {
+        self.debts[player]
+    }
+
+    /// Peg `points` for `player`, stopping at the target. Points owed are paid off first.
+    ///
+    /// Fails once the game is over, or when nothing is pegged: the back peg has to move ahead of
+    /// the front one.
+    pub fn peg(&mut self, player: usize, points: u8) -> Result<()> {
+        if player >= self.players() {
+            return Err(anyhow!("no player {player}"));
+        }
+        if let Some(finisher) = self.finisher() {
+            return Err(anyhow!("player {finisher} already reached {}", self.target));
+        }
+        if points == 0 {
+            return Err(anyhow!("nothing to peg"));
+        }
+
+        let debt = self.debts[player];
+        self.debts[player] = debt.saturating_sub(points);
+        let points = points.saturating_sub(debt);
+        if points == 0 {
+            return Ok(());
+        }
+
+        let front = self.fronts[player];
+        self.backs[player] = front;
+        self.fronts[player] = front.saturating_add(points).min(self.target);
+        Ok(())
+    }
+
+    /// Move `player`'s front peg `points` holes back, paying for the crib in auction games. The
+    /// back peg follows when it was ahead. Points past the first hole are owed.
+    ///
+    /// Fails once the game is over.
+    pub fn pay(&mut self, player: usize, points: u8) -> Result<()> {
+        if player >= self.players() {
+            return Err(anyhow!("no player {player}"));
+        }
+        if let Some(finisher) = self.finisher() {
+            return Err(anyhow!("player {finisher} already reached {}", self.target));
+        }
+        let front = self.fronts[player];
+        let owed = points.saturating_sub(front);
+        self.debts[player] = self.debts[player]
+            .checked_add(owed)
+            .ok_or_else(|| anyhow!("player {player} can't owe more than {}", u8::MAX))?;
+
+        self.fronts[player] = front.saturating_sub(points);
+        self.backs[player] = self.backs[player].min(self.fronts[player]);
+        Ok(())
+    }
+
+    /// Peg `player` to `hole`, which must be ahead of their front peg.
+    pub fn peg_to(&mut self, player: usize, hole: u8) -> Result<()> {
+        let front = self.fronts.get(player).copied().unwrap_or_default();
+        if hole <= front {
+            return Err(anyhow!(
+                "player {player} can't peg hole {hole}, their front peg is on {front}"
+            ));
+        }
+        self.peg(player, hole - front)
+    }
+
+    /// The player who reached the target, ending the game, if any.
+    pub fn finisher(&self) -> Option<usize> {
+        self.fronts.iter().position(|score| *score >= self.target)
+    }
+
+    /// The winner once the game is over: the player who reached the target, or in lowball the one
+    /// furthest from it.
+    pub fn winner(&self) -> Option<usize> {
+        let finisher = self.finisher()?;
+        match self.objective {
+            Objective::Most => Some(finisher),
+            Objective::Fewest => (0..self.players())
+                .filter(|player| *player != finisher)
+                .min_by_key(|player| self.fronts[*player]),
+        }
+    }
+
+    /// Skunk of a finished game, judged on the best score short of the target: the best loser's,
+    /// or in lowball the winner's, skunking the player who reached it.
+    pub fn skunk(&self) -> Option<Skunk> {
+        let finisher = self.finisher()?;
+        let best_other = (0..self.players())
+            .filter(|player| *player != finisher)
+            .map(|player| self.fronts[player])
+            .max()
+            .unwrap_or_default();
+        Some(Skunk::of(best_other, self.target))
+    }
+
+    /// Game points the winner takes in match play, once the game is won.
+    pub fn game_points(&self) -> Option<u8> {
+        self.skunk().map(Skunk::game_points)
+    }
+
+    /// The board as text, a street of 60 holes per 60 points of the target for each player,
+    /// labelled from `names`. The last street runs on to the target's hole, one street holding
+    /// the whole of shorter games.
+    ///
+    /// Both pegs are drawn. The skunk lines sit between the groups of 5 holes, so they aren't
+    /// marked.
+    pub fn render(&self, names: &[&str], style: CardStyle) -> Vec<String> {
+        let (hole, peg) = match style {
+            CardStyle::Ascii => ('.', 'o'),
+            _ => ('·', '●'),
+        };
+
+        let mut lines = Vec::new();
+        for player in 0..self.players() {
+            let (front, back) = self.pegs(player);
+            let streets = (self.target as usize / STREET).max(1);
+            for street in 0..streets {
+                let last = match street + 1 == streets {
+                    true => self.target as usize,
+                    false => (street + 1) * STREET,
+                };
+                let groups = (street * STREET + 1..=last)
+                    .map(|score| {
+                        if score == front as usize || score == back as usize {
+                            peg
+                        } else {
+                            hole
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .chunks(5)
+                    .map(|group| group.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let label = match street {
+                    0 => {
+                        let name = names.get(player).copied().unwrap_or_default();
+                        let score = match self.debts[player] {
+                            0 => front.to_string(),
+                            debt => format!("-{debt}"),
+                        };
+                        format!("{name: <4}{score: >3}")
+                    }
+                    _ => String::new(),
+                };
+                lines.push(format!("{label: <8}{groups}"));
+            }
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leapfrogs() -> Result<()> {
+        let mut board = Board::new(2);
+        board.peg(0, 7)?;
+        assert_eq!((7, 0), board.pegs(0));
+        board.peg(0, 4)?;
+        assert_eq!((11, 7), board.pegs(0));
+        board.peg_to(1, 20)?;
+        assert_eq!(&[11, 20], board.scores());
+
+        assert!(board.peg(0, 0).is_err());
+        assert!(board.peg_to(1, 20).is_err());
+        assert!(board.peg_to(1, 3).is_err());
+        assert!(board.peg(2, 1).is_err());
+        assert_eq!(&[11, 20], board.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn stops_at_game() -> Result<()> {
+        let mut board = Board::new(2);
+        board.peg_to(0, 118)?;
+        board.peg_to(1, 90)?;
+        assert_eq!(None, board.winner());
+        assert_eq!(None, board.game_points());
+
+        board.peg(0, 12)?;
+        assert_eq!((GAME_POINTS, 118), board.pegs(0));
+        assert_eq!(Some(0), board.winner());
+        assert!(board.peg(1, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn skunk_lines() {
+        assert_eq!(Skunk::None, Skunk::of(91, GAME_POINTS));
+        assert_eq!(Skunk::Single, Skunk::of(90, GAME_POINTS));
+        assert_eq!(Skunk::Single, Skunk::of(61, GAME_POINTS));
+        assert_eq!(Skunk::Double, Skunk::of(60, GAME_POINTS));
+        assert_eq!(Skunk::None, Skunk::of(31, 61));
+        assert_eq!(Skunk::Single, Skunk::of(30, 61));
+        assert_eq!(Skunk::Double, Skunk::of(120, 181));
+
+        let mut board = Board::new(3);
+        board.peg_to(1, 60).unwrap();
+        board.peg_to(2, 95).unwrap();
+        board.peg_to(0, GAME_POINTS).unwrap();
+        // The best loser saves the others
+        assert_eq!(Some(Skunk::None), board.skunk());
+        assert_eq!(Some(1), board.game_points());
+    }
+
+    #[test]
+    fn renders_pegs() -> Result<()> {
+        let mut board = Board::new(2);
+        board.peg(0, 3)?;
+        board.peg(0, 4)?;
+        board.peg_to(1, 65)?;
+
+        let lines = board.render(&["You", "Bot"], CardStyle::Ascii);
+        assert_eq!(4, lines.len());
+        assert_eq!(
+            "You   7 ..o.. .o... ..... ..... ..... ..... ..... ..... ..... ..... ..... .....",
+            lines[0]
+        );
+        assert!(lines[2].starts_with("Bot  65 ..... "));
+        assert!(lines[3].starts_with("        ....o "));
+        Ok(())
+    }
+
+    #[test]
+    fn short_games() -> Result<()> {
+        let mut board = Board::with_target(2, 61);
+        board.peg_to(1, 29)?;
+        board.peg(0, 70)?;
+        assert_eq!(&[61, 29], board.scores());
+        assert_eq!(Some(0), board.winner());
+        assert_eq!(Some(Skunk::Single), board.skunk());
+        assert_eq!(2, board.render(&["You", "Bot"], CardStyle::Ascii).len());
+
+        let board = Board::with_target(2, 181);
+        assert_eq!(6, board.render(&["You", "Bot"], CardStyle::Ascii).len());
+        Ok(())
+    }
+
+    #[test]
+    fn renders_the_target_hole() -> Result<()> {
+        let mut board = Board::new(2);
+        board.peg_to(0, 118)?;
+        board.peg_to(0, GAME_POINTS)?;
+        let lines = board.render(&["You", "Bot"], CardStyle::Ascii);
+        assert_eq!(4, lines.len());
+        assert!(lines[1].ends_with(" ..... ..o.. o"), "{}", lines[1]);
+        assert!(lines[3].ends_with(" ..... ..... ."), "{}", lines[3]);
+
+        let mut board = Board::with_target(2, 61);
+        board.peg_to(1, 61)?;
+        let lines = board.render(&["You", "Bot"], CardStyle::Ascii);
+        assert_eq!(2, lines.len());
+        assert!(lines[0].ends_with(" ..... ."));
+        assert!(lines[1].starts_with("Bot  61 ..... "));
+        assert!(lines[1].ends_with(" ..... o"));
+
+        let board = Board::with_target(1, 31);
+        let lines = board.render(&["You"], CardStyle::Ascii);
+        assert_eq!("You   0 ..... ..... ..... ..... ..... ..... .", lines[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn pays_back() -> Result<()> {
+        let mut board = Board::new(2);
+        board.peg(0, 7)?;
+        board.peg(0, 4)?;
+        board.pay(0, 2)?;
+        assert_eq!((9, 7), board.pegs(0));
+        board.pay(0, 5)?;
+        assert_eq!((4, 4), board.pegs(0));
+
+        // Past the first hole, points are owed until scored back
+        board.pay(0, 7)?;
+        assert_eq!(((0, 0), 3), (board.pegs(0), board.debt(0)));
+        assert!(board.render(&["You"], CardStyle::Ascii)[0].starts_with("You  -3 ....."));
+        board.peg(0, 2)?;
+        assert_eq!(((0, 0), 1), (board.pegs(0), board.debt(0)));
+        board.peg(0, 6)?;
+        assert_eq!(((5, 0), 0), (board.pegs(0), board.debt(0)));
+
+        assert!(board.pay(2, 1).is_err());
+        board.pay(1, 0)?;
+        assert_eq!(&[5, 0], board.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn lowball_reaching_the_target_loses() -> Result<()> {
+        let mut board = Board::new(2).with_objective(Objective::Fewest);
+        board.peg_to(1, 50)?;
+        board.peg_to(0, 119)?;
+        assert_eq!(None, board.winner());
+
+        board.peg(0, 5)?;
+        assert_eq!(Some(0), board.finisher());
+        assert_eq!(Some(1), board.winner());
+        assert_eq!(Some(Skunk::Double), board.skunk());
+        assert!(board.peg(1, 2).is_err());
+        Ok(())
+    }
+}
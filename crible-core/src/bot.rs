@@ -0,0 +1,277 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::SeedableRng;
+
+use crate::{
+    crib_worth, score_play, BidState, Card, CardStyle, DiscardRanking, DiscardState, Objective,
+    PeggingState, Player, Position, WinModel, WinTable, GAME_POINTS,
+};
+
+/// Throws and plays at random, as a baseline for the other strategies.
+pub struct RandomPlayer {
+    rng: RefCell<StdRng>,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        let hand = state.hand();
+        if hand.len() < state.discards() {
+            return Err(anyhow!(
+                "{} cards can't be thrown from {}",
+                state.discards(),
+                hand.len()
+            ));
+        }
+        Ok(hand
+            .choose_multiple(&mut *self.rng.borrow_mut(), state.discards())
+            .copied()
+            .collect())
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        Ok(state.playable().choose(&mut *self.rng.borrow_mut()))
+    }
+}
+
+/// Keeps the hand with the best mean over every starter, then lays its lowest card.
+#[derive(Default)]
+pub struct GreedyShow;
+
+impl Player for GreedyShow {
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        best_mean_discard(state.hand(), state.discards(), state.objective())
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        Ok(state.playable().min_by_key(|card| (card.value(), *card)))
+    }
+}
+
+/// Throws at random, then lays the card scoring the most points right away.
+pub struct GreedyPegging {
+    random: RandomPlayer,
+}
+
+impl GreedyPegging {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            random: RandomPlayer::new(seed),
+        }
+    }
+}
+
+impl Player for GreedyPegging {
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        self.random.discard(state)
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        Ok(greedy_play(state))
+    }
+}
+
+/// Computer opponent: keeps the hand with the best mean and pegs greedily. In auction games, it
+/// bids for the crib as long as the bid stays below what the crib is worth to it.
+///
+/// Within 30 points of the end of a two-player game to 121, it keeps the hand with the best
+/// chances of winning in `WinTable::standard` instead: pegging or starving the crib can matter
+/// more than the show then.
+#[derive(Default)]
+pub struct Bot;
+
+/// Points from the target under which `Bot` discards for the win rather than the mean: about two
+/// hands.
+const ENDGAME_POINTS: u8 = 30;
+
+impl Player for Bot {
+    fn bid(&self, state: &BidState) -> Result<Option<u8>> {
+        let hand = state.hand();
+        let kept = hand.len().saturating_sub(state.discards());
+        // Sampled from a fixed seed, so the same deal gets the same bids
+        let worth = crib_worth(hand, kept, 200, &mut StdRng::seed_from_u64(0))?;
+
+        let bid = state.high_bid().map_or(0, |high| high + 1);
+        Ok((bid as f32 <= worth && bid <= state.max_bid()).then_some(bid))
+    }
+
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        endgame_discard(state, WinTable::standard)
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        Ok(greedy_play(state))
+    }
+}
+
+/// `Bot`'s discard: the best mean, or within `ENDGAME_POINTS` of the end the best chances of
+/// winning by the `model`, only built then.
+fn endgame_discard<'a, M: WinModel + 'a>(
+    state: &DiscardState,
+    model: impl FnOnce() -> &'a M,
+) -> Result<Vec<Card>> {
+    let scores = state.scores();
+    let endgame = state.players() == 2
+        && state.hand().len() == 6
+        && state.discards() == 2
+        && state.objective() == Objective::Most
+        && state.target() == GAME_POINTS
+        && scores.own.max(scores.opponent) + ENDGAME_POINTS >= GAME_POINTS;
+    if !endgame {
+        return best_mean_discard(state.hand(), state.discards(), state.objective());
+    }
+
+    let mut ranking = DiscardRanking::new(state.hand(), &[])?;
+    let position = Position {
+        scores,
+        is_dealer: state.is_dealer(),
+    };
+    // Sampled from a fixed seed, so the same deal gets the same discard
+    let mut rng = StdRng::seed_from_u64(0);
+    ranking.rank_by_win(state.hand(), position, model(), 300, &mut rng)?;
+    Ok(ranking.best().discard.clone())
+}
+
+/// The `discards` cards to throw from 5 to 7 to keep the hand of 3 to 5 with the best mean for
+/// `objective`, the best of `DiscardRanking`: the highest, or in lowball the lowest.
+pub fn best_mean_discard(
+    hand: &[Card],
+    discards: usize,
+    objective: Objective,
+) -> Result<Vec<Card>> {
+    if !(5..=7).contains(&hand.len()) || !hand.iter().all_unique() {
+        return Err(anyhow!(
+            "expected 5 to 7 different cards to discard from, got {}",
+            CardStyle::Ascii.cards(hand)
+        ));
+    }
+    let kept = hand.len().saturating_sub(discards);
+    if !(3..=5).contains(&kept) {
+        return Err(anyhow!("can't keep {kept} cards out of {}", hand.len()));
+    }
+
+    let ranking = DiscardRanking::keeping(hand, &[], kept)?.with_objective(objective);
+    Ok(ranking.best().discard.clone())
+}
+
+/// The card scoring the most points right away, avoiding leaving a count of 5 or 21. In lowball,
+/// the card scoring the fewest, leaving those counts if it can.
+pub fn greedy_play(state: &PeggingState) -> Option<Card> {
+    let count = state.count();
+    let objective = state.objective();
+
+    state.playable().max_by_key(|card| {
+        let mut pile = state.pile().to_vec();
+        pile.push(*card);
+        let points: u8 = score_play(&pile)
+            .into_iter()
+            .map(|peg| state.rules().peg_points(peg))
+            .sum();
+
+        // Don't leave the opponent an easy 15 or 31, unless they are better off without it.
+        let after = count + card.value();
+        let risky = after == 5 || after == 21;
+
+        (
+            points as i32 * objective.sign(),
+            risky == (objective == Objective::Fewest),
+            card.value(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_cards, play_game, Game, PegAction, Pegging, ScoreDistributions, Scores};
+
+    fn state_for(pegging: &Pegging, player: usize) -> PeggingState<'_> {
+        let starter = parse_cards("Ac").unwrap()[0];
+        PeggingState::new(player, pegging, starter, &[], false, Scores::default())
+    }
+
+    #[test]
+    fn greedy_plays_for_points() {
+        let hands = vec![parse_cards("Th").unwrap(), parse_cards("Kh 5s 9d").unwrap()];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+        pegging
+            .act(0, PegAction::Play(parse_cards("Th").unwrap()[0]))
+            .unwrap();
+
+        let five = parse_cards("5s").unwrap()[0];
+        assert_eq!(Some(five), greedy_play(&state_for(&pegging, 1)));
+        let lowball = state_for(&pegging, 1).with_objective(Objective::Fewest);
+        assert_ne!(Some(five), greedy_play(&lowball));
+        assert_eq!(
+            Some(five),
+            GreedyShow.play(&state_for(&pegging, 1)).unwrap()
+        );
+    }
+
+    #[test]
+    fn greedy_show_keeps_best_hand() {
+        let hand = parse_cards("5d 5h 5s Jc 2h 9s").unwrap();
+        let state = DiscardState::new(&hand, 2, false, false, Scores::default(), 2);
+        let discard = GreedyShow.discard(&state).unwrap();
+        assert_eq!(parse_cards("2h 9s").unwrap(), discard);
+    }
+
+    #[test]
+    fn bot_discards_for_the_win_at_the_end() {
+        let hand = parse_cards("5h 5c 5s Jd Ac 2d").unwrap();
+        let state = |own, opponent| {
+            let scores = Scores { own, opponent };
+            DiscardState::new(&hand, 2, false, false, scores, 2)
+        };
+
+        // A small table is enough to tell the keeps apart
+        let table = WinTable::new(&ScoreDistributions::simulate(500, 0));
+        let discard = |state| endgame_discard(&state, || &table).unwrap();
+
+        let best_mean = best_mean_discard(&hand, 2, Objective::Most).unwrap();
+        assert_eq!(best_mean, discard(state(60, 60)));
+        assert_ne!(best_mean, discard(state(119, 119)));
+        // Games to 61 aren't in the table
+        assert_eq!(best_mean, discard(state(59, 59).with_target(61)));
+    }
+
+    #[test]
+    fn random_player_is_legal() {
+        let player = RandomPlayer::new(3);
+        let hand = parse_cards("5d 5h 5s Jc 2h 9s").unwrap();
+        let state = DiscardState::new(&hand, 2, false, false, Scores::default(), 2);
+        let discard = player.discard(&state).unwrap();
+        assert_eq!(2, discard.len());
+        assert!(discard[0] != discard[1] && discard.iter().all(|card| hand.contains(card)));
+    }
+
+    #[test]
+    fn strategies_play_full_games() {
+        let random = RandomPlayer::new(1);
+        let greedy_pegging = GreedyPegging::new(2);
+
+        for seed in 0..3 {
+            let mut game = Game::new(seed);
+            let winner = play_game(&mut game, &[&random, &greedy_pegging]).unwrap();
+            assert_eq!(Some(winner), game.winner());
+
+            let mut game = Game::new(seed);
+            play_game(&mut game, &[&GreedyShow, &Bot]).unwrap();
+
+            let mut game = Game::new_variant(crate::Variant::Lowball, seed);
+            let winner = play_game(&mut game, &[&random, &Bot]).unwrap();
+            assert_eq!(crate::GAME_POINTS, game.scores()[1 - winner]);
+        }
+    }
+}
@@ -0,0 +1,554 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+use crate::{score_kept, Card, Deck, Number, Objective, ShowRules, Suit};
+
+/// Most starters left: 3 cards dealt from a deck with 2 jokers.
+const MAX_STARTERS: usize = 54 - 3;
+
+/// Score of a hand for every starter that can still be cut.
+pub struct StarterScores {
+    scores: [(Card, u8); MAX_STARTERS],
+    len: u8,
+    sorted: bool,
+}
+
+impl StarterScores {
+    pub fn new() -> Self {
+        StarterScores {
+            scores: [(Card::new(Number::A, Suit::H), 0); MAX_STARTERS],
+            len: 0,
+            sorted: true,
+        }
+    }
+
+    pub fn push(&mut self, card: Card, score: u8) {
+        self.sorted = false;
+        self.scores[self.len as usize] = (card, score);
+        self.len += 1;
+    }
+
+    /// Sort by score, best first.
+    pub fn sort(&mut self) {
+        if !self.sorted {
+            self.scores[0..self.len as usize].sort_by(|(_, a), (_, b)| b.cmp(a));
+            self.sorted = true;
+        }
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.iter().map(|(_, score)| score as u32).sum::<u32>() as f32 / self.len as f32
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Card, u8)> + '_ {
+        self.scores[0..self.len as usize].iter().copied()
+    }
+}
+
+impl Default for StarterScores {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One way of splitting the dealt cards: the hand kept and the cards thrown to the crib.
+pub struct Keep {
+    /// The cards kept: 4, 3 in five-card games or 5 in seven-card ones.
+    pub hand: Vec<Card>,
+    pub discard: Vec<Card>,
+    pub scores: StarterScores,
+    /// Probability of winning the game with this keep, once ranked with
+    /// `DiscardRanking::rank_by_win`.
+    pub win: Option<f32>,
+    /// Mean points of the crib for the player's team, negative when it's the opponents', once
+    /// ranked with `DiscardRanking::rank_with_crib`.
+    pub crib: Option<f32>,
+}
+
+impl Keep {
+    /// Mean points of the hand, plus the crib's once estimated.
+    pub fn value(&self) -> f32 {
+        self.scores.mean() + self.crib.unwrap_or(0.0)
+    }
+}
+
+/// Every possible keep for a deal, best mean first: the highest, or in lowball the lowest.
+pub struct DiscardRanking {
+    pub(crate) keeps: Vec<Keep>,
+    objective: Objective,
+    jokers: usize,
+    known: Vec<Card>,
+}
+
+impl DiscardRanking {
+    /// Rank every way of keeping 4 of the `dealt` cards.
+    ///
+    /// Starters are enumerated from the deck minus the dealt cards and every `known` card
+    /// (e.g. a card flashed by accident), since none of them can be cut. Crib estimates leave
+    /// them out too.
+    pub fn new(dealt: &[Card], known: &[Card]) -> Result<Self> {
+        Self::keeping(dealt, known, 4)
+    }
+
+    /// Rank every way of keeping `kept` of the `dealt` cards: 4, 3 in five-card games or 5 in
+    /// seven-card ones. At most 2 cards are thrown.
+    pub fn keeping(dealt: &[Card], known: &[Card], kept: usize) -> Result<Self> {
+        Self::with_jokers(dealt, known, kept, 0)
+    }
+
+    /// Rank every way of keeping `kept` of the `dealt` cards, played with a deck of 52 cards plus
+    /// `jokers`. Jokers dealt, known or cut count as the card scoring the most in each hand.
+    pub fn with_jokers(dealt: &[Card], known: &[Card], kept: usize, jokers: usize) -> Result<Self> {
+        if !(3..=5).contains(&kept) {
+            return Err(anyhow!("3 to 5 cards are kept, not {kept}"));
+        }
+        if !(kept..=kept + 2).contains(&dealt.len()) {
+            return Err(anyhow!(
+                "{kept} to {} cards expected, {} given",
+                kept + 2,
+                dealt.len()
+            ));
+        }
+
+        let seen = dealt
+            .iter()
+            .chain(known)
+            .filter(|card| card.is_joker())
+            .count();
+        let plural = |n| if n == 1 { "joker" } else { "jokers" };
+        match jokers {
+            0 if seen > 0 => {
+                return Err(anyhow!(
+                    "{seen} {} seen, but the deck has no jokers",
+                    plural(seen)
+                ))
+            }
+            _ if seen > jokers => {
+                return Err(anyhow!(
+                    "{seen} jokers seen, but the deck has only {jokers} {}",
+                    plural(jokers)
+                ))
+            }
+            _ => {}
+        }
+
+        let mut deck = Deck::with_jokers(jokers, &mut thread_rng())?;
+        deck.remove(dealt);
+        deck.remove(known);
+        if deck.is_empty() {
+            return Err(anyhow!("no starter left to cut: every other card is known"));
+        }
+
+        let mut keeps: Vec<Keep> = Vec::new();
+
+        // All possible combinaisons of the cards kept
+        for hand in dealt.iter().copied().combinations(kept) {
+            let discard = dealt
+                .iter()
+                .copied()
+                .filter(|card| !hand.contains(card))
+                .collect();
+
+            let mut scores = StarterScores::new();
+            for starter in deck.cards().copied() {
+                scores.push(starter, score_kept(&hand, starter, ShowRules::HAND));
+            }
+            scores.sort();
+
+            keeps.push(Keep {
+                hand,
+                discard,
+                scores,
+                win: None,
+                crib: None,
+            })
+        }
+
+        let mut ranking = Self {
+            keeps,
+            objective: Objective::Most,
+            jokers,
+            known: known.to_vec(),
+        };
+        ranking.sort_by_value();
+
+        Ok(ranking)
+    }
+
+    /// The same keeps ranked for `objective`: in lowball, the fewest points first.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self.sort_by_value();
+        self
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Jokers in the deck, on top of the 52 cards.
+    pub fn jokers(&self) -> usize {
+        self.jokers
+    }
+
+    /// Cards known to be out of the deck, other than the ones dealt.
+    pub fn known(&self) -> &[Card] {
+        &self.known
+    }
+
+    /// Rank the keeps by the hand's mean plus the crib's when it belongs to the player's team, as
+    /// the dealer or the dealer's partner, minus it otherwise. The crib is estimated by
+    /// `crib_mean` over `samples` draws.
+    ///
+    /// Fails when too many cards are known to fill the crib and cut a starter.
+    pub fn rank_with_crib(
+        &mut self,
+        dealt: &[Card],
+        owns_crib: bool,
+        samples: u32,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        let sign = if owns_crib { 1.0 } else { -1.0 };
+        let pool = unseen(dealt, &self.known, self.jokers);
+        for keep in self.keeps.iter_mut() {
+            keep.crib = Some(sign * crib_mean_from(&keep.discard, &pool, samples, rng)?);
+        }
+        self.sort_by_value();
+        Ok(())
+    }
+
+    pub fn best(&self) -> &Keep {
+        &self.keeps[0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keeps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keeps.is_empty()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Keep> {
+        self.keeps.iter()
+    }
+
+    /// Sort by value for the objective, keeping the order of ties.
+    fn sort_by_value(&mut self) {
+        let sign = self.objective.sign() as f32;
+        self.keeps
+            .sort_by(|a, b| (sign * b.value()).total_cmp(&(sign * a.value())));
+    }
+}
+
+/// Expected points owning the crib is worth to a player dealt `dealt` and keeping `kept`: the
+/// value of their best keep throwing to their own crib, minus the best throwing to the opponent's.
+/// Cribs are estimated by `crib_mean` over `samples` draws.
+///
+/// A rational bid for the crib in auction cribbage is anything up to it.
+pub fn crib_worth(dealt: &[Card], kept: usize, samples: u32, rng: &mut impl Rng) -> Result<f32> {
+    let mut ours = DiscardRanking::keeping(dealt, &[], kept)?;
+    ours.rank_with_crib(dealt, true, samples, rng)?;
+    let mut theirs = DiscardRanking::keeping(dealt, &[], kept)?;
+    theirs.rank_with_crib(dealt, false, samples, rng)?;
+
+    Ok(ours.best().value() - theirs.best().value())
+}
+
+/// Mean score of a crib holding `discard`, estimated from `samples` random draws of the 2 other
+/// crib cards and the starter among the cards not `dealt`.
+///
+/// The other discards are drawn uniformly: the opponent's actual throws aren't modelled.
+pub fn crib_mean(
+    discard: &[Card],
+    dealt: &[Card],
+    samples: u32,
+    rng: &mut impl Rng,
+) -> Result<f32> {
+    crib_mean_from(discard, &unseen(dealt, &[], 0), samples, rng)
+}
+
+/// Cards that can still be dealt to the others or cut: a deck of 52 cards plus `jokers`, 2 at
+/// most, minus the `dealt` and `known` ones. Sorted, so draws from them only depend on the rng.
+pub(crate) fn unseen(dealt: &[Card], known: &[Card], jokers: usize) -> Vec<Card> {
+    let mut deck = Deck::with_jokers(jokers, &mut thread_rng()).expect("2 jokers at most");
+    deck.remove(dealt);
+    deck.remove(known);
+    let mut pool = deck.cards().copied().collect::<Vec<_>>();
+    pool.sort();
+    pool
+}
+
+/// `crib_mean` drawing the other crib cards and the starter from `pool`.
+fn crib_mean_from(
+    discard: &[Card],
+    pool: &[Card],
+    samples: u32,
+    rng: &mut impl Rng,
+) -> Result<f32> {
+    let needed = 5usize.saturating_sub(discard.len());
+    if pool.len() < needed {
+        return Err(anyhow!(
+            "too many known cards to fill the crib and cut a starter: {needed} needed, {} left",
+            pool.len()
+        ));
+    }
+
+    let mut total = 0u32;
+    for _ in 0..samples {
+        let mut drawn = pool.choose_multiple(rng, needed).copied();
+        let starter = drawn.next().expect("enough cards left");
+        let mut crib = discard.to_vec();
+        crib.extend(drawn);
+        total += score_kept(&crib, starter, ShowRules::CRIB) as u32;
+    }
+
+    Ok(total as f32 / samples.max(1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    #[test]
+    fn ranks_best_keep_first() -> Result<()> {
+        let ranking = DiscardRanking::new(&parse_cards("5d 5h 5s Jc 2h 9s")?, &[])?;
+
+        assert_eq!(15, ranking.len());
+        assert_eq!(parse_cards("5d 5h 5s Jc")?, ranking.best().hand);
+        assert_eq!(parse_cards("2h 9s")?, ranking.best().discard);
+        assert_eq!(46, ranking.best().scores.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn crib_mean_favors_fives() -> Result<()> {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let dealt = parse_cards("5d 5h 8s Kc 2h 9s")?;
+        let mut rng = StdRng::seed_from_u64(0);
+        let fives = crib_mean(&parse_cards("5d 5h")?, &dealt, 2000, &mut rng)?;
+        let wide = crib_mean(&parse_cards("Kc 2h")?, &dealt, 2000, &mut rng)?;
+        assert!(fives > wide + 3.0, "{fives} vs {wide}");
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            fives,
+            crib_mean(&parse_cards("5d 5h")?, &dealt, 2000, &mut rng)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn crib_changes_sides() -> Result<()> {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let dealt = parse_cards("5d 5h Ks Qc 3h 9s")?;
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut ours = DiscardRanking::new(&dealt, &[])?;
+        ours.rank_with_crib(&dealt, true, 500, &mut rng)?;
+        let mut theirs = DiscardRanking::new(&dealt, &[])?;
+        theirs.rank_with_crib(&dealt, false, 500, &mut rng)?;
+
+        // Fives are better thrown to our own crib than to the opponents'
+        let fives = parse_cards("5d 5h")?;
+        let rank = |ranking: &DiscardRanking| ranking.iter().position(|keep| keep.discard == fives);
+        assert!(rank(&ours) < rank(&theirs));
+        assert!(!theirs.best().discard.iter().any(|card| card.value() == 5));
+        assert!(ours.best().crib.unwrap() > 0.0);
+        assert!(theirs.best().crib.unwrap() < 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn crib_worth_counts_both_cribs() -> Result<()> {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // Owning the crib both adds a crib and spares one: about twice a crib's mean
+        let dealt = parse_cards("Ad 3h 7s 9c Jh Kd")?;
+        let worth = crib_worth(&dealt, 4, 500, &mut StdRng::seed_from_u64(0))?;
+        assert!((6.0..12.0).contains(&worth), "{worth}");
+        assert_eq!(
+            worth,
+            crib_worth(&dealt, 4, 500, &mut StdRng::seed_from_u64(0))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn jokers_count_as_their_best_card() -> Result<()> {
+        let dealt = parse_cards("5d 5h 5s Jk 2h 9s")?;
+        let err = DiscardRanking::new(&dealt, &[]).err().unwrap();
+        assert_eq!("1 joker seen, but the deck has no jokers", err.to_string());
+
+        let mut ranking = DiscardRanking::with_jokers(&dealt, &[], 4, 1)?;
+        assert_eq!(53 - 6, ranking.best().scores.len());
+        assert_eq!(parse_cards("5d 5h 5s Jk")?, ranking.best().hand);
+        let standard = DiscardRanking::new(&parse_cards("5d 5h 5s Jc 2h 9s")?, &[])?;
+        assert!(ranking.best().value() > standard.best().value());
+
+        ranking.rank_with_crib(&dealt, true, 100, &mut rand::thread_rng())?;
+        assert!(ranking.best().crib.is_some());
+
+        // Every starter of a 54-card deck
+        let ranking = DiscardRanking::with_jokers(&parse_cards("5d 5h 5s")?, &[], 3, 2)?;
+        assert_eq!(54 - 3, ranking.best().scores.len());
+        assert!(DiscardRanking::with_jokers(&dealt, &[], 4, 3).is_err());
+        let err = DiscardRanking::with_jokers(&dealt, &[Card::JOKERS[1]], 4, 1)
+            .err()
+            .unwrap();
+        assert_eq!(
+            "2 jokers seen, but the deck has only 1 joker",
+            err.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lowball_ranks_fewest_first() -> Result<()> {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let dealt = parse_cards("5d 5h 5s Jc 2h Ks")?;
+        let mut ranking = DiscardRanking::new(&dealt, &[])?.with_objective(Objective::Fewest);
+
+        assert_eq!(Objective::Fewest, ranking.objective());
+        assert!(ranking
+            .iter()
+            .zip(ranking.iter().skip(1))
+            .all(|(a, b)| a.value() <= b.value()));
+        assert_eq!(
+            parse_cards("5d 5h 5s Jc")?,
+            ranking.iter().last().unwrap().hand
+        );
+
+        let mut rng = StdRng::seed_from_u64(0);
+        ranking.rank_with_crib(&dealt, false, 200, &mut rng)?;
+        // Feeding the opponents' crib is welcome: fives are thrown
+        assert!(ranking.best().discard.iter().all(|card| card.value() == 5));
+        Ok(())
+    }
+
+    #[test]
+    fn five_card_deals_throw_one() -> Result<()> {
+        let ranking = DiscardRanking::new(&parse_cards("5d 5h 5s Jc 9s")?, &[])?;
+
+        assert_eq!(5, ranking.len());
+        assert_eq!(parse_cards("9s")?, ranking.best().discard);
+        assert!(ranking.iter().all(|keep| keep.scores.len() == 47));
+
+        Ok(())
+    }
+
+    #[test]
+    fn five_card_deals_keep_three() -> Result<()> {
+        let ranking = DiscardRanking::keeping(&parse_cards("5d 5h Jc 2h 9s")?, &[], 3)?;
+
+        assert_eq!(10, ranking.len());
+        assert_eq!(parse_cards("5d 5h Jc")?, ranking.best().hand);
+        assert_eq!(parse_cards("2h 9s")?, ranking.best().discard);
+        assert!(ranking.iter().all(|keep| keep.scores.len() == 47));
+        assert!(DiscardRanking::keeping(&parse_cards("5d 5h Jc 2h 9s")?, &[], 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn seven_card_deals_keep_five() -> Result<()> {
+        let ranking = DiscardRanking::keeping(&parse_cards("5d 5h 5s Jc Td 2h 9s")?, &[], 5)?;
+
+        assert_eq!(21, ranking.len());
+        assert_eq!(parse_cards("5d 5h 5s Jc Td")?, ranking.best().hand);
+        assert!(ranking.iter().all(|keep| keep.scores.len() == 45));
+
+        Ok(())
+    }
+
+    #[test]
+    fn known_cards_stay_out_of_the_crib() -> Result<()> {
+        let dealt = parse_cards("5d 5h 5s 5c Ac 2c")?;
+        // Every other card is known but 3 kings: they make the crib and the starter
+        let kings = parse_cards("Kh Kd Ks")?;
+        let known = Deck::new_shuffled()
+            .cards()
+            .copied()
+            .filter(|card| !dealt.contains(card) && !kings.contains(card))
+            .collect::<Vec<_>>();
+
+        let mut ranking = DiscardRanking::new(&dealt, &known)?;
+        ranking.rank_with_crib(&dealt, true, 200, &mut rand::thread_rng())?;
+        let fives = ranking
+            .iter()
+            .find(|keep| keep.discard.iter().all(|card| card.value() == 5))
+            .unwrap();
+        // A pair of 5s with 3 kings, whichever is cut
+        assert_eq!(Some(8.0 + 12.0), fives.crib);
+
+        // With 2 kings left, the crib can't be filled
+        let mut known = known;
+        known.push(kings[0]);
+        let mut ranking = DiscardRanking::new(&dealt, &known)?;
+        assert!(ranking
+            .rank_with_crib(&dealt, true, 10, &mut rand::thread_rng())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn known_cards_are_not_starters() -> Result<()> {
+        let known = parse_cards("5c Jh")?;
+        let ranking = DiscardRanking::new(&parse_cards("5d 5h 5s Jc 2h 9s")?, &known)?;
+
+        for keep in ranking.iter() {
+            assert_eq!(44, keep.scores.len());
+            assert!(keep
+                .scores
+                .iter()
+                .all(|(starter, _)| !known.contains(&starter)));
+        }
+
+        // Without the last 5, 29 is out of reach.
+        let (_, best_score) = ranking.best().scores.iter().next().unwrap();
+        assert_eq!(22, best_score);
+
+        Ok(())
+    }
+
+    #[test]
+    fn needs_a_starter_left() -> Result<()> {
+        let dealt = parse_cards("5d 5h 5s Jc 2h 9s")?;
+        let mut known = Deck::new_shuffled()
+            .cards()
+            .copied()
+            .filter(|card| !dealt.contains(card))
+            .collect::<Vec<_>>();
+        assert!(DiscardRanking::new(&dealt, &known).is_err());
+
+        known.pop();
+        assert_eq!(1, DiscardRanking::new(&dealt, &known)?.best().scores.len());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_deal() -> Result<()> {
+        assert!(DiscardRanking::new(&parse_cards("5d 5h 5s")?, &[]).is_err());
+        assert!(DiscardRanking::new(&parse_cards("5d 5h 5s Jc 2h 9s Kd")?, &[]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,517 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::discard::unseen;
+use crate::{
+    best_mean_discard, greedy_play, Card, Deck, DiscardRanking, Hand, Number, Objective, PegAction,
+    Pegging, PeggingState, Scores, GAME_POINTS,
+};
+
+/// Chances of winning a game from a given score, before a hand is dealt.
+pub trait WinModel {
+    /// Probability that the player with `own` points beats the player with `opponent` points,
+    /// `own_deals` telling whether they deal the next hand.
+    fn win_probability(&self, own: u8, opponent: u8, own_deals: bool) -> f64;
+}
+
+/// Rough model of a game as a race between two players scoring normally distributed points.
+///
+/// A dealer scores about 17 points in a hand counting the crib, a pone about 10.5, each give or
+/// take 6.5. Good enough to tell a race from a lost cause, not to split hairs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RaceModel;
+
+const DEALER_MEAN: f64 = 17.0;
+const PONE_MEAN: f64 = 10.5;
+const HAND_DEVIATION: f64 = 6.5;
+
+impl WinModel for RaceModel {
+    fn win_probability(&self, own: u8, opponent: u8, own_deals: bool) -> f64 {
+        let own_needs = GAME_POINTS.saturating_sub(own) as f64;
+        let opponent_needs = GAME_POINTS.saturating_sub(opponent) as f64;
+        if own_needs == 0.0 {
+            return 1.0;
+        }
+        if opponent_needs == 0.0 {
+            return 0.0;
+        }
+
+        // Hands left until someone gets there, and the dealer's edge in the coming one
+        let hands = (own_needs + opponent_needs) / (DEALER_MEAN + PONE_MEAN);
+        let edge = (DEALER_MEAN - PONE_MEAN) / 2.0;
+        let lead = opponent_needs - own_needs + if own_deals { edge } else { -edge };
+        let deviation = HAND_DEVIATION * (2.0 * hands.max(0.5)).sqrt();
+
+        normal_cdf(lead / deviation)
+    }
+}
+
+/// Standard normal cumulative distribution, from the Abramowitz and Stegun approximation of erf.
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+
+    match x >= 0.0 {
+        true => 0.5 * (1.0 + erf),
+        false => 0.5 * (1.0 - erf),
+    }
+}
+
+/// How many points each side scores in a hand, measured by simulating hands between players
+/// keeping the best mean and pegging greedily.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScoreDistributions {
+    /// Probability of the dealer pegging `pegging[n][_]` points and the pone `pegging[_][n]`,
+    /// his heels included.
+    pegging: Vec<Vec<f64>>,
+    pone_hand: Vec<f64>,
+    /// Dealer's hand and crib together.
+    dealer_show: Vec<f64>,
+}
+
+impl ScoreDistributions {
+    /// Deal and play `hands` hands shuffled from `seed`.
+    pub fn simulate(hands: u32, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut pegging = vec![vec![0.0; 32]; 32];
+        let mut pone_hand = vec![0.0; 30];
+        let mut dealer_show = vec![0.0; 59];
+
+        const PONE: usize = 0;
+        const DEALER: usize = 1;
+        for _ in 0..hands {
+            let mut deck = Deck::new_shuffled_with(&mut rng);
+            let mut kept = [Vec::new(), Vec::new()];
+            let mut crib = Vec::with_capacity(4);
+            for kept in kept.iter_mut() {
+                let dealt = (0..6).map(|_| deck.draw()).collect::<Vec<_>>();
+                let discard = best_mean_discard(&dealt, 2, Objective::Most).expect("6 cards dealt");
+                kept.extend(dealt.iter().filter(|card| !discard.contains(card)));
+                crib.extend(discard);
+            }
+            let starter = deck.draw();
+
+            let mut pegged = [0usize; 2];
+            if starter.number() == Number::J {
+                pegged[DEALER] += 2;
+            }
+            let mut play = Pegging::new(kept.to_vec(), PONE).expect("no jokers in the deck");
+            while let Some(player) = play.to_play() {
+                let state =
+                    PeggingState::new(player, &play, starter, &[], false, Scores::default());
+                let action = match greedy_play(&state) {
+                    Some(card) => PegAction::Play(card),
+                    None => PegAction::Go,
+                };
+                for (scorer, peg) in play.act(player, action).expect("legal action") {
+                    pegged[scorer] += peg.points() as usize;
+                }
+            }
+
+            let hand = |player: usize| Hand::from_slice(&kept[player]).expect("4 cards kept");
+            let show = hand(DEALER).score(starter, false)
+                + Hand::from_slice(&crib)
+                    .expect("4 cards thrown")
+                    .score(starter, true);
+            pegging[pegged[DEALER].min(31)][pegged[PONE].min(31)] += 1.0;
+            pone_hand[hand(PONE).score(starter, false) as usize] += 1.0;
+            dealer_show[show as usize] += 1.0;
+        }
+
+        // Someone always pegs the last card, a hand scoring nothing would stall the tables
+        pegging[0][0] = 0.0;
+        let pegged: f64 = pegging.iter().flatten().sum();
+        pegging.iter_mut().for_each(|row| scale(row, pegged));
+        scale(&mut pone_hand, hands as f64);
+        scale(&mut dealer_show, hands as f64);
+
+        Self {
+            pegging,
+            pone_hand,
+            dealer_show,
+        }
+    }
+
+    /// Mean points pegged by the dealer and the pone, then counted by the pone and the dealer.
+    pub fn means(&self) -> [f64; 4] {
+        let mean = |dist: &[f64]| dist.iter().enumerate().map(|(n, p)| n as f64 * p).sum();
+        let dealer_pegging = self
+            .pegging
+            .iter()
+            .map(|row| row.iter().sum())
+            .collect::<Vec<_>>();
+        let pone_pegging = (0..self.pegging.len())
+            .map(|pone| self.pegging.iter().map(|row| row[pone]).sum())
+            .collect::<Vec<_>>();
+
+        [
+            mean(&dealer_pegging),
+            mean(&pone_pegging),
+            mean(&self.pone_hand),
+            mean(&self.dealer_show),
+        ]
+    }
+}
+
+/// Turn counts out of `total` into probabilities.
+fn scale(counts: &mut [f64], total: f64) {
+    if total > 0.0 {
+        counts.iter_mut().for_each(|count| *count /= total);
+    }
+}
+
+/// Win probability of every score of a 121-point game, for the dealer and the pone.
+///
+/// Solved by dynamic programming over `ScoreDistributions`, a hand at a time: pegging first, the
+/// pone's hand, then the dealer's hand and crib. A player reaching 121 wins on the spot. When
+/// both would get there during the play, it's a coin flip.
+pub struct WinTable {
+    /// Indexed by `index(own, opponent, own_deals)`.
+    table: Vec<f64>,
+}
+
+const SCORES: usize = GAME_POINTS as usize;
+
+fn index(own: usize, opponent: usize, own_deals: bool) -> usize {
+    (own * SCORES + opponent) * 2 + own_deals as usize
+}
+
+impl WinTable {
+    pub fn new(distributions: &ScoreDistributions) -> Self {
+        let ScoreDistributions {
+            pegging,
+            pone_hand,
+            dealer_show,
+        } = distributions;
+        let mut table = vec![0.0; SCORES * SCORES * 2];
+        // Win probability after the play, before the show
+        let mut after_play = vec![0.0; SCORES * SCORES * 2];
+
+        let won = |table: &[f64], own: usize, opponent: usize, own_deals: bool| match (
+            own >= SCORES,
+            opponent >= SCORES,
+        ) {
+            (true, _) => 1.0,
+            (false, true) => 0.0,
+            (false, false) => table[index(own, opponent, own_deals)],
+        };
+
+        // Every hand scores a point or more, so games only move to a higher total: solve from the
+        // highest down. The show can score nothing, so a total needs the hands before the shows.
+        for total in (0..2 * SCORES - 1).rev() {
+            let states = (total.saturating_sub(SCORES - 1)..=total.min(SCORES - 1))
+                .map(|own| (own, total - own))
+                .collect::<Vec<_>>();
+
+            for &(own, opponent) in &states {
+                for own_deals in [false, true] {
+                    let mut win = 0.0;
+                    for (dealer, row) in pegging.iter().enumerate() {
+                        for (pone, p) in row.iter().enumerate().filter(|(_, p)| **p > 0.0) {
+                            let (mine, theirs) = if own_deals {
+                                (dealer, pone)
+                            } else {
+                                (pone, dealer)
+                            };
+                            let (own, opponent) = (own + mine, opponent + theirs);
+                            win += p * match (own >= SCORES, opponent >= SCORES) {
+                                (true, true) => 0.5,
+                                _ => won(&after_play, own, opponent, own_deals),
+                            };
+                        }
+                    }
+                    table[index(own, opponent, own_deals)] = win;
+                }
+            }
+
+            for &(own, opponent) in &states {
+                for own_deals in [false, true] {
+                    let mut win = 0.0;
+                    for (pone_points, p) in pone_hand.iter().enumerate().filter(|(_, p)| **p > 0.0)
+                    {
+                        let (own, opponent) = match own_deals {
+                            true => (own, opponent + pone_points),
+                            false => (own + pone_points, opponent),
+                        };
+                        if own >= SCORES || opponent >= SCORES {
+                            win += p * won(&table, own, opponent, !own_deals);
+                            continue;
+                        }
+
+                        for (dealer_points, q) in
+                            dealer_show.iter().enumerate().filter(|(_, q)| **q > 0.0)
+                        {
+                            let (own, opponent) = match own_deals {
+                                true => (own + dealer_points, opponent),
+                                false => (own, opponent + dealer_points),
+                            };
+                            win += p * q * won(&table, own, opponent, !own_deals);
+                        }
+                    }
+                    after_play[index(own, opponent, own_deals)] = win;
+                }
+            }
+        }
+
+        Self { table }
+    }
+
+    /// The table for the distributions of 10,000 simulated hands, computed once.
+    pub fn standard() -> &'static Self {
+        static STANDARD: OnceLock<WinTable> = OnceLock::new();
+        STANDARD.get_or_init(|| Self::new(&ScoreDistributions::simulate(10_000, 0)))
+    }
+}
+
+impl WinModel for WinTable {
+    fn win_probability(&self, own: u8, opponent: u8, own_deals: bool) -> f64 {
+        match (own >= GAME_POINTS, opponent >= GAME_POINTS) {
+            (true, _) => 1.0,
+            (false, true) => 0.0,
+            (false, false) => self.table[index(own as usize, opponent as usize, own_deals)],
+        }
+    }
+}
+
+/// Scores and deal before the discard, from the point of view of the player discarding.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Position {
+    pub scores: Scores,
+    pub is_dealer: bool,
+}
+
+impl DiscardRanking {
+    /// Rank the keeps by the probability of winning the game from `position`, rather than by
+    /// the hand's mean.
+    ///
+    /// The hand is played out `samples` times: the starter, the opponent's cards and crib
+    /// discards are drawn at random from the cards neither dealt nor known, both sides peg
+    /// greedily, then the hands and the crib are counted in order. The first to 121 wins,
+    /// otherwise `model` tells the chances from the new score. Near the end of a game, this
+    /// favors keeps that peg well or hold back cards that feed the dealer. Every keep is tried on
+    /// the same draws.
+    pub fn rank_by_win(
+        &mut self,
+        dealt: &[Card],
+        position: Position,
+        model: &impl WinModel,
+        samples: u32,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        if dealt.len() != 6 {
+            return Err(anyhow!("win probabilities need the 6 cards dealt"));
+        }
+        if self.objective() != Objective::Most {
+            return Err(anyhow!(
+                "win probabilities are for games to 121, not lowball"
+            ));
+        }
+        if self.jokers() > 0 {
+            return Err(anyhow!("win probabilities are for decks without jokers"));
+        }
+
+        let pool = unseen(dealt, self.known(), 0);
+        if pool.len() < 7 {
+            return Err(anyhow!("too many known cards to deal the opponent a hand"));
+        }
+
+        let mut wins = vec![0.0; self.keeps.len()];
+        for _ in 0..samples {
+            let drawn = pool.choose_multiple(rng, 7).copied().collect::<Vec<_>>();
+            let (starter, opponent_discard) = (drawn[0], &drawn[1..3]);
+            let opponent_hand = Hand::from_slice(&drawn[3..])?;
+
+            for (keep, wins) in self.keeps.iter().zip(wins.iter_mut()) {
+                let mut crib = keep.discard.clone();
+                crib.extend_from_slice(opponent_discard);
+                *wins += play_out(
+                    position,
+                    &Hand::from_slice(&keep.hand)?,
+                    &opponent_hand,
+                    &Hand::from_slice(&crib)?,
+                    starter,
+                    model,
+                );
+            }
+        }
+
+        for (keep, wins) in self.keeps.iter_mut().zip(wins) {
+            keep.win = Some((wins / samples.max(1) as f64) as f32);
+        }
+        self.keeps
+            .sort_by(|a, b| b.win.unwrap_or(0.0).total_cmp(&a.win.unwrap_or(0.0)));
+
+        Ok(())
+    }
+}
+
+/// Probability of winning once the hand is over, 1 or 0 if someone gets to 121 during it.
+fn play_out(
+    position: Position,
+    own: &Hand,
+    opponent: &Hand,
+    crib: &Hand,
+    starter: Card,
+    model: &impl WinModel,
+) -> f64 {
+    const ME: usize = 0;
+    const THEM: usize = 1;
+    let dealer = if position.is_dealer { ME } else { THEM };
+    let pone = 1 - dealer;
+    let mut scores = [position.scores.own, position.scores.opponent];
+
+    // Whether `player` won by scoring `points`
+    let mut score = |player: usize, points: u8| {
+        scores[player] = scores[player].saturating_add(points);
+        scores[player] >= GAME_POINTS
+    };
+    let outcome = |player: usize| if player == ME { 1.0 } else { 0.0 };
+
+    if starter.number() == Number::J && score(dealer, 2) {
+        return outcome(dealer);
+    }
+
+    let hands = vec![own.cards().to_vec(), opponent.cards().to_vec()];
+    let mut pegging = Pegging::new(hands, pone).expect("hands hold no jokers");
+    while let Some(player) = pegging.to_play() {
+        let state = PeggingState::new(player, &pegging, starter, &[], false, Scores::default());
+        let action = match greedy_play(&state) {
+            Some(card) => PegAction::Play(card),
+            None => PegAction::Go,
+        };
+        for (scorer, peg) in pegging.act(player, action).expect("legal action") {
+            if score(scorer, peg.points()) {
+                return outcome(scorer);
+            }
+        }
+    }
+
+    let hands = [own, opponent];
+    for (player, points) in [
+        (pone, hands[pone].score(starter, false)),
+        (dealer, hands[dealer].score(starter, false)),
+        (dealer, crib.score(starter, true)),
+    ] {
+        if score(player, points) {
+            return outcome(player);
+        }
+    }
+
+    model.win_probability(scores[ME], scores[THEM], !position.is_dealer)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::parse_cards;
+
+    #[test]
+    fn race_model() {
+        let model = RaceModel;
+        let even = model.win_probability(0, 0, true);
+        assert!(0.5 < even && even < 0.6, "{even}");
+        assert!((even + model.win_probability(0, 0, false) - 1.0).abs() < 1e-6);
+
+        assert!(model.win_probability(100, 60, false) > 0.8);
+        assert!(model.win_probability(60, 100, true) < 0.2);
+        assert_eq!(1.0, model.win_probability(121, 120, false));
+        assert_eq!(0.0, model.win_probability(120, 121, true));
+    }
+
+    #[test]
+    fn win_table() {
+        let distributions = ScoreDistributions::simulate(500, 0);
+        let [dealer_pegging, pone_pegging, pone_hand, dealer_show] = distributions.means();
+        assert!(dealer_pegging > pone_pegging);
+        assert!(dealer_show > pone_hand + 3.0);
+        assert!((6.0..10.0).contains(&pone_hand), "{pone_hand}");
+
+        let table = WinTable::new(&distributions);
+        let first_deal = table.win_probability(0, 0, true);
+        assert!(0.5 < first_deal && first_deal < 0.6, "{first_deal}");
+        assert!((first_deal + table.win_probability(0, 0, false) - 1.0).abs() < 1e-9);
+
+        // Both players' chances add up
+        for (own, opponent) in [(120, 120), (115, 90), (60, 100)] {
+            for own_deals in [false, true] {
+                let total = table.win_probability(own, opponent, own_deals)
+                    + table.win_probability(opponent, own, !own_deals);
+                assert!((total - 1.0).abs() < 1e-9, "{own}-{opponent}: {total}");
+            }
+        }
+
+        assert!(table.win_probability(100, 60, false) > 0.85);
+        assert!(table.win_probability(110, 100, true) > table.win_probability(100, 110, true));
+        assert_eq!(1.0, table.win_probability(121, 3, false));
+    }
+
+    #[test]
+    fn pegging_wins_at_the_end() -> Result<()> {
+        // Three 5s and a jack score big mid-game, but at 119-119 the pone must peg 2 before the
+        // dealer does, and the jack pegs nothing.
+        let dealt = parse_cards("5h 5c 5s Jd Ac 2d")?;
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let mean_best = DiscardRanking::new(&dealt, &[])?;
+        let mut ranking = DiscardRanking::new(&dealt, &[])?;
+        let position = Position {
+            scores: Scores {
+                own: 119,
+                opponent: 119,
+            },
+            is_dealer: false,
+        };
+        ranking.rank_by_win(&dealt, position, &RaceModel, 300, &mut rng)?;
+
+        assert!(ranking.iter().all(|keep| keep.win.is_some()));
+        let best = ranking.best();
+        assert_ne!(mean_best.best().hand, best.hand);
+        assert!(best.win.unwrap() > 0.3);
+
+        // Far from the end, the best mean wins too
+        let mut early = DiscardRanking::new(&dealt, &[])?;
+        let position = Position {
+            scores: Scores::default(),
+            is_dealer: false,
+        };
+        early.rank_by_win(&dealt, position, &RaceModel, 300, &mut rng)?;
+        assert_eq!(mean_best.best().hand, early.best().hand);
+        Ok(())
+    }
+
+    #[test]
+    fn known_cards_are_never_drawn() -> Result<()> {
+        let dealt = parse_cards("5d 5h 5s 5c Ac 2c")?;
+        // Only tens and faces are left: the opponent's hand and the starter always hold a pair,
+        // enough for them to win at the show
+        let known = Deck::new_shuffled()
+            .cards()
+            .copied()
+            .filter(|card| !dealt.contains(card) && card.value() != 10)
+            .collect::<Vec<_>>();
+        let position = Position {
+            scores: Scores {
+                own: 0,
+                opponent: 119,
+            },
+            is_dealer: true,
+        };
+
+        let mut ranking = DiscardRanking::new(&dealt, &known)?;
+        let mut rng = StdRng::seed_from_u64(0);
+        ranking.rank_by_win(&dealt, position, &RaceModel, 50, &mut rng)?;
+        assert!(ranking.iter().all(|keep| keep.win == Some(0.0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,517 @@
+//! Line-based text protocol between crible and cribbage engines, in the spirit of UCI for chess.
+//!
+//! The host runs the game and the engine takes decisions for one seat. Cards are written as in
+//! `CardStyle::Ascii` (`5h`, `Td`, `Ks`), fields are separated by spaces. The host sends:
+//!
+//! - `crible`: sent once on startup. The engine answers `id name <name>`, then `cribleok`.
+//! - `isready`: the engine answers `readyok` once it's done with earlier messages.
+//! - `newgame`: a new game starts.
+//! - `scores <own> <opponent>`: the scores, sent before each deal.
+//! - `deal <dealer|pone> <6 cards>`: cards dealt to the engine and whether it deals.
+//! - `discard`: the engine answers `discard <card> <card>` with the 2 cards it throws.
+//! - `starter <card>`: the starter was cut, the play begins.
+//! - `opponent <card>` or `opponent go`: what the opponent did during the play.
+//! - `play`: the engine's turn. It answers `play <card>`, or `go` when it can't play.
+//! - `quit`: the engine exits.
+//!
+//! The protocol covers standard two-player games only. Every opponent action is sent before the
+//! next `play`. Engines answer `error <message>` to messages they don't understand; other lines
+//! starting with `info` are ignored by the host.
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    no_jokers, parse_cards, Card, CardStyle, DiscardState, Number, PegAction, Pegging,
+    PeggingState, Player, Scores, Suit,
+};
+
+/// The engine's seat, from its own point of view.
+const ME: usize = 0;
+const OPPONENT: usize = 1;
+
+/// Speaks the engine side of the protocol, taking decisions with a `Player`.
+pub struct Engine {
+    name: String,
+    player: Box<dyn Player>,
+    scores: Scores,
+    is_dealer: bool,
+    hand: Vec<Card>,
+    discards: Vec<Card>,
+    starter: Option<Card>,
+    pegging: Option<Pegging>,
+}
+
+impl Engine {
+    pub fn new(name: &str, player: Box<dyn Player>) -> Self {
+        Self {
+            name: name.to_string(),
+            player,
+            scores: Scores::default(),
+            is_dealer: false,
+            hand: Vec::new(),
+            discards: Vec::new(),
+            starter: None,
+            pegging: None,
+        }
+    }
+
+    /// Answer messages read from `input` until `quit` or the end of the input.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim() == "quit" {
+                break;
+            }
+
+            let replies = match self.handle(&line) {
+                Ok(replies) => replies,
+                Err(err) => vec![format!("error {err}")],
+            };
+            for reply in replies {
+                writeln!(output, "{reply}")?;
+            }
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle one message, returning the lines to answer.
+    pub fn handle(&mut self, line: &str) -> Result<Vec<String>> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Vec::new());
+        };
+        let rest = words.collect::<Vec<_>>().join(" ");
+
+        match command {
+            "crible" => Ok(vec![
+                format!("id name {}", self.name),
+                "cribleok".to_string(),
+            ]),
+            "isready" => Ok(vec!["readyok".to_string()]),
+            "newgame" => {
+                self.player.new_game()?;
+                self.scores = Scores::default();
+                Ok(Vec::new())
+            }
+            "scores" => {
+                let (own, opponent) = rest
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("expected 2 scores: {rest}"))?;
+                self.scores = Scores {
+                    own: own.parse()?,
+                    opponent: opponent.parse()?,
+                };
+                Ok(Vec::new())
+            }
+            "deal" => {
+                let (role, cards) = rest
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("expected dealer or pone and 6 cards: {rest}"))?;
+                self.is_dealer = match role {
+                    "dealer" => true,
+                    "pone" => false,
+                    _ => return Err(anyhow!("expected dealer or pone: {role}")),
+                };
+                let hand = parse_cards(cards)?;
+                if hand.len() != 6 {
+                    return Err(anyhow!("expected 6 cards, got {}", hand.len()));
+                }
+                no_jokers(&hand)?;
+                self.hand = hand;
+                self.discards.clear();
+                self.starter = None;
+                self.pegging = None;
+                Ok(Vec::new())
+            }
+            "discard" => {
+                if self.hand.len() != 6 {
+                    return Err(anyhow!("no cards to discard from"));
+                }
+                let state = DiscardState::new(
+                    &self.hand,
+                    2,
+                    self.is_dealer,
+                    self.is_dealer,
+                    self.scores,
+                    2,
+                );
+                let discard = self.player.discard(&state)?;
+                if discard.len() != 2 || !discard.iter().all(|card| self.hand.contains(card)) {
+                    return Err(anyhow!(
+                        "can't discard {}",
+                        CardStyle::Ascii.cards(&discard)
+                    ));
+                }
+                self.hand.retain(|card| !discard.contains(card));
+                let reply = format!("discard {}", CardStyle::Ascii.cards(&discard));
+                self.discards = discard;
+                Ok(vec![reply])
+            }
+            "starter" => {
+                if self.hand.len() != 4 {
+                    return Err(anyhow!("discard before the starter"));
+                }
+                let starter = parse_cards(&rest)?;
+                let [starter] = starter[..] else {
+                    return Err(anyhow!("expected 1 starter: {rest}"));
+                };
+                no_jokers(&[starter])?;
+                self.starter = Some(starter);
+                let first = if self.is_dealer { OPPONENT } else { ME };
+                self.pegging = Some(Pegging::new(vec![self.hand.clone(), unknown(4)], first)?);
+                Ok(Vec::new())
+            }
+            "opponent" => {
+                let pegging = self
+                    .pegging
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("no play under way"))?;
+                let left = pegging.hand(OPPONENT).len();
+                if left == 0 {
+                    return Err(anyhow!("the opponent has no cards left"));
+                }
+
+                // Only the played cards are known: stand-ins fill the rest of the hand.
+                let action = match rest.as_str() {
+                    "go" => {
+                        *pegging = pegging.with_hand(OPPONENT, unknown(left))?;
+                        PegAction::Go
+                    }
+                    card => {
+                        let card = parse_cards(card)?;
+                        let [card] = card[..] else {
+                            return Err(anyhow!("expected 1 card: {rest}"));
+                        };
+                        let mut hand = unknown(left - 1);
+                        hand.push(card);
+                        *pegging = pegging.with_hand(OPPONENT, hand)?;
+                        PegAction::Play(card)
+                    }
+                };
+                pegging.act(OPPONENT, action)?;
+                Ok(Vec::new())
+            }
+            "play" => {
+                let pegging = self
+                    .pegging
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("no play under way"))?;
+                let starter = self.starter.ok_or_else(|| anyhow!("no starter"))?;
+                if pegging.to_play() != Some(ME) {
+                    return Err(anyhow!("not the engine's turn"));
+                }
+
+                let state = PeggingState::new(
+                    ME,
+                    pegging,
+                    starter,
+                    &self.discards,
+                    self.is_dealer,
+                    self.scores,
+                );
+                let action = match self.player.play(&state)? {
+                    Some(card) => PegAction::Play(card),
+                    None => PegAction::Go,
+                };
+
+                let pegging = self.pegging.as_mut().expect("checked above");
+                pegging.act(ME, action)?;
+                Ok(vec![match action {
+                    PegAction::Play(card) => format!("play {}", CardStyle::Ascii.card(card)),
+                    PegAction::Go => "go".to_string(),
+                }])
+            }
+            _ => Err(anyhow!("unknown command: {command}")),
+        }
+    }
+}
+
+/// Stand-ins for `n` cards the engine can't see. Kings, so a go is always legal when it could be.
+fn unknown(n: usize) -> Vec<Card> {
+    vec![Card::new(Number::K, Suit::S); n]
+}
+
+/// A player whose decisions are taken by an engine speaking the protocol, usually another
+/// process.
+pub struct EnginePlayer {
+    name: String,
+    io: RefCell<EngineIo>,
+    child: Option<Child>,
+}
+
+struct EngineIo {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    /// Actions of the current play already sent to the engine.
+    sent: usize,
+    starter_sent: bool,
+}
+
+impl EnginePlayer {
+    /// Start the engine `command`, e.g. `python3 bot.py`, and wait for it to identify.
+    pub fn spawn(command: &str) -> Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| anyhow!("empty engine command"))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| anyhow!("can't start engine {command}: {err}"))?;
+
+        let input = BufReader::new(child.stdout.take().expect("piped stdout"));
+        let output = child.stdin.take().expect("piped stdin");
+        let mut player = Self::with_io(input, output)?;
+        player.child = Some(child);
+        Ok(player)
+    }
+
+    /// Talk to an engine reading from `output` and writing to `input`.
+    pub fn with_io(input: impl BufRead + 'static, output: impl Write + 'static) -> Result<Self> {
+        let mut io = EngineIo {
+            input: Box::new(input),
+            output: Box::new(output),
+            sent: 0,
+            starter_sent: false,
+        };
+
+        io.send("crible")?;
+        let mut name = String::from("engine");
+        loop {
+            let line = io.receive()?;
+            if let Some(id) = line.strip_prefix("id name ") {
+                name = id.to_string();
+            } else if line == "cribleok" {
+                break;
+            }
+        }
+
+        Ok(Self {
+            name,
+            io: RefCell::new(io),
+            child: None,
+        })
+    }
+
+    /// Name the engine gave itself.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl EngineIo {
+    fn send(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "{message}")?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Next meaningful line from the engine.
+    fn receive(&mut self) -> Result<String> {
+        loop {
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Err(anyhow!("engine exited"));
+            }
+
+            let line = line.trim();
+            if let Some(message) = line.strip_prefix("error") {
+                return Err(anyhow!("engine error:{message}"));
+            }
+            if !line.is_empty() && !line.starts_with("info") {
+                return Ok(line.to_string());
+            }
+        }
+    }
+
+    /// Send `message` and return the argument of the engine's answer starting with `reply`.
+    fn ask(&mut self, message: &str, reply: &str) -> Result<String> {
+        self.send(message)?;
+        let line = self.receive()?;
+        match line.split_once(' ') {
+            Some((word, rest)) if word == reply => Ok(rest.to_string()),
+            None if line == reply => Ok(String::new()),
+            _ => Err(anyhow!("expected {reply} from the engine, got: {line}")),
+        }
+    }
+}
+
+impl Player for EnginePlayer {
+    fn new_game(&self) -> Result<()> {
+        self.io.borrow_mut().send("newgame")
+    }
+
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        if state.players() != 2 || state.hand().len() != 6 || state.discards() != 2 {
+            return Err(anyhow!("engines only play standard two-player games"));
+        }
+        let (hand, is_dealer, scores) = (state.hand(), state.is_dealer(), state.scores());
+        let mut io = self.io.borrow_mut();
+        io.sent = 0;
+        io.starter_sent = false;
+
+        io.send(&format!("scores {} {}", scores.own, scores.opponent))?;
+        let role = if is_dealer { "dealer" } else { "pone" };
+        io.send(&format!("deal {role} {}", CardStyle::Ascii.cards(hand)))?;
+
+        let discard = parse_cards(&io.ask("discard", "discard")?)?;
+        match discard.len() {
+            2 => Ok(discard),
+            n => Err(anyhow!("engine discarded {n} cards")),
+        }
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        let mut io = self.io.borrow_mut();
+        if !io.starter_sent {
+            io.send(&format!(
+                "starter {}",
+                CardStyle::Ascii.card(state.starter())
+            ))?;
+            io.starter_sent = true;
+        }
+
+        let history = state.history();
+        for (player, action) in &history[io.sent..] {
+            if *player != state.player() {
+                io.send(&match action {
+                    PegAction::Play(card) => format!("opponent {}", CardStyle::Ascii.card(*card)),
+                    PegAction::Go => "opponent go".to_string(),
+                })?;
+            }
+        }
+        // The engine's own answer will be part of the history next time
+        io.sent = history.len() + 1;
+
+        io.send("play")?;
+        let line = io.receive()?;
+        match line.split_once(' ') {
+            None if line == "go" => Ok(None),
+            Some(("play", card)) => match parse_cards(card)?[..] {
+                [card] => Ok(Some(card)),
+                _ => Err(anyhow!("engine played more than 1 card: {card}")),
+            },
+            _ => Err(anyhow!("expected play or go from the engine, got: {line}")),
+        }
+    }
+}
+
+/// Time an engine gets to exit once told to quit, before it's killed.
+const QUIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl Drop for EnginePlayer {
+    fn drop(&mut self) {
+        let _ = self.io.borrow_mut().send("quit");
+        let Some(child) = self.child.as_mut() else {
+            return;
+        };
+
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while matches!(child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if let Ok(None) = child.try_wait() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::pipe;
+
+    use super::*;
+    use crate::{play_game, Bot, Game, GreedyShow};
+
+    fn replies(engine: &mut Engine, line: &str) -> Vec<String> {
+        engine.handle(line).unwrap()
+    }
+
+    #[test]
+    fn engine_answers() {
+        let mut engine = Engine::new("test", Box::new(GreedyShow));
+        assert_eq!(
+            vec!["id name test", "cribleok"],
+            replies(&mut engine, "crible")
+        );
+        assert!(replies(&mut engine, "newgame").is_empty());
+        replies(&mut engine, "scores 10 20");
+        assert!(engine.handle("play").is_err());
+
+        replies(&mut engine, "deal pone 5d 5h 5s Jc 2h 9s");
+        assert_eq!(vec!["discard 2h 9s"], replies(&mut engine, "discard"));
+        replies(&mut engine, "starter Kc");
+
+        // The pone leads its lowest card
+        assert_eq!(vec!["play 5h"], replies(&mut engine, "play"));
+        replies(&mut engine, "opponent Td");
+        assert_eq!(vec!["play 5d"], replies(&mut engine, "play"));
+        replies(&mut engine, "opponent Kd");
+        assert_eq!(vec!["go"], replies(&mut engine, "play"));
+        assert!(engine.handle("play").is_err());
+        replies(&mut engine, "opponent go");
+        assert_eq!(vec!["play 5s"], replies(&mut engine, "play"));
+        replies(&mut engine, "opponent 3c");
+        assert_eq!(vec!["play Jc"], replies(&mut engine, "play"));
+        assert!(engine.handle("play").is_err());
+
+        assert!(engine.handle("bogus").is_err());
+    }
+
+    #[test]
+    fn engine_rejects_jokers() {
+        let mut engine = Engine::new("test", Box::new(Bot));
+        assert!(engine.handle("deal pone Jk 5h 5d 5s Jc 2h").is_err());
+        assert!(engine.handle("discard").is_err());
+
+        replies(&mut engine, "deal pone 5d 5h 5s Jc 2h 9s");
+        replies(&mut engine, "discard");
+        assert!(engine.handle("starter Jk").is_err());
+        replies(&mut engine, "starter Kc");
+        replies(&mut engine, "play");
+        assert!(engine.handle("opponent Jk").is_err());
+        replies(&mut engine, "opponent Td");
+    }
+
+    #[test]
+    fn plays_against_engine() -> Result<()> {
+        let (engine_input, host_output) = pipe()?;
+        let (host_input, engine_output) = pipe()?;
+        let engine = thread::spawn(move || {
+            Engine::new("bot", Box::new(Bot)).run(BufReader::new(engine_input), engine_output)
+        });
+
+        let player = EnginePlayer::with_io(BufReader::new(host_input), host_output)?;
+        assert_eq!("bot", player.name());
+        for seed in 0..3 {
+            let mut game = Game::new(seed);
+            let winner = play_game(&mut game, &[&player, &GreedyShow])?;
+            assert_eq!(Some(winner), game.winner());
+        }
+
+        drop(player);
+        engine.join().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_engines_ignoring_quit() -> Result<()> {
+        // Says cribleok forever, whatever it's told
+        let player = EnginePlayer::spawn("yes cribleok")?;
+        let start = Instant::now();
+        drop(player);
+        assert!(start.elapsed() < QUIT_TIMEOUT * 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,1008 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    no_jokers, score_kept, BidState, Board, Card, CardStyle, Deck, DiscardState, Event, GameRecord,
+    Number, Peg, PegAction, Pegging, PeggingState, RuleSet, Scores, ShowRules, Variant,
+};
+
+/// Points needed to win a game.
+pub const GAME_POINTS: u8 = 121;
+
+/// Highest bid for the crib in auction games: no crib scores more.
+pub const MAX_BID: u8 = 29;
+
+/// Why points were scored.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reason {
+    /// Dealer turning a jack as the starter.
+    HisHeels,
+    /// The first pone's head start in five-card games.
+    ThreeForLast,
+    Peg(Peg),
+    Hand,
+    Crib,
+    /// Points an opponent missed counting their hand or crib.
+    Muggins,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::HisHeels => write!(f, "his heels"),
+            Reason::ThreeForLast => write!(f, "three for last"),
+            Reason::Peg(peg) => write!(f, "{peg}"),
+            Reason::Hand => write!(f, "hand"),
+            Reason::Crib => write!(f, "crib"),
+            Reason::Muggins => write!(f, "muggins"),
+        }
+    }
+}
+
+impl FromStr for Reason {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "his heels" => Ok(Reason::HisHeels),
+            "three for last" => Ok(Reason::ThreeForLast),
+            "hand" => Ok(Reason::Hand),
+            "crib" => Ok(Reason::Crib),
+            "muggins" => Ok(Reason::Muggins),
+            _ => Ok(Reason::Peg(s.parse()?)),
+        }
+    }
+}
+
+/// Points scored by a player.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Points {
+    pub player: usize,
+    pub points: u8,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Auction games: waiting for `Game::to_bid` to bid for the crib or pass.
+    Bid,
+    /// Waiting for every player to throw to the crib.
+    Discard,
+    /// Waiting for `Pegging::to_play` to play or say go.
+    Pegging,
+    /// Playing muggins, waiting for `Game::to_count` to count their hand or crib.
+    Count,
+    /// Hands and crib were counted, waiting for `Game::next_hand`.
+    Show,
+    /// Someone reached the game's target.
+    Over,
+}
+
+/// A game of cribbage, from the first deal to 121, or 61 in five-card games and 181 in seven-card
+/// ones, for the players of its `Variant`.
+///
+/// The game is a state machine driven by the players' decisions: `bid` during `Phase::Bid` in
+/// auction games, `discard` during `Phase::Discard`, `peg` during `Phase::Pegging`, `count`
+/// during `Phase::Count` when playing muggins, and `next_hand` once the show is done. Each step
+/// returns the points it scored. The game ends as soon as a player reaches the target, even in
+/// the middle of the play or the show.
+pub struct Game {
+    rng: StdRng,
+    deck: Deck,
+    /// Decks to deal the next hands from before shuffling from the seed.
+    stacked: VecDeque<Deck>,
+    variant: Variant,
+    rules: RuleSet,
+    board: Board,
+    dealer: usize,
+    /// Player whose crib it is: the dealer, unless another player won it at auction.
+    crib_owner: usize,
+    /// Auction games: the player to bid and the highest bid so far, with its bidder.
+    bidder: usize,
+    high_bid: Option<(usize, u8)>,
+    /// Cards dealt to each player, minus the discards once thrown.
+    hands: Vec<Vec<Card>>,
+    /// Cards each player threw to the crib.
+    discards: Vec<Vec<Card>>,
+    crib: Vec<Card>,
+    starter: Option<Card>,
+    pegging: Option<Pegging>,
+    phase: Phase,
+    /// Hands left to count when playing muggins, in order: the player, and whether it's the crib.
+    counts: VecDeque<(usize, bool)>,
+    /// Everything that happened since the first deal.
+    record: GameRecord,
+}
+
+impl Game {
+    /// Start a game shuffled from `seed`, cutting for the first deal.
+    ///
+    /// The cut doesn't draw from the deck's shuffles: the game deals the same cards as
+    /// `with_dealer` given the same seed and the dealer it cut.
+    pub fn new(seed: u64) -> Self {
+        Self::new_variant(Variant::Standard, seed)
+    }
+
+    /// Start a game of `variant` shuffled from `seed`, cutting for the first deal.
+    pub fn new_variant(variant: Variant, seed: u64) -> Self {
+        let dealer = StdRng::seed_from_u64(!seed).gen_range(0..variant.players());
+        Self::with_variant(variant, seed, dealer)
+    }
+
+    /// Start a game shuffled from `seed`, `dealer` dealing the first hand.
+    pub fn with_dealer(seed: u64, dealer: usize) -> Self {
+        Self::with_variant(Variant::Standard, seed, dealer)
+    }
+
+    /// Start a game of `variant` shuffled from `seed`, `dealer` dealing the first hand.
+    pub fn with_variant(variant: Variant, seed: u64, dealer: usize) -> Self {
+        Self::with_rules(variant, RuleSet::for_variant(variant), seed, dealer)
+    }
+
+    /// Start a game of `variant` under `rules`, shuffled from `seed`, `dealer` dealing the first
+    /// hand.
+    pub fn with_rules(variant: Variant, rules: RuleSet, seed: u64, dealer: usize) -> Self {
+        Self::dealing(variant, rules, seed, dealer, VecDeque::new())
+    }
+
+    /// Start a game of `variant` under `rules`, `dealer` dealing the first hand, each hand dealt
+    /// from the next of `decks`: the cards of a game played over the board. Once they run out,
+    /// hands are shuffled from `seed`.
+    ///
+    /// Each deck must hold the cards of a deal and the starter.
+    pub fn with_decks(
+        variant: Variant,
+        rules: RuleSet,
+        seed: u64,
+        dealer: usize,
+        decks: Vec<Deck>,
+    ) -> Result<Self> {
+        let needed = variant.dealt() * variant.players() + variant.crib_from_deck() + 1;
+        for deck in &decks {
+            no_jokers(&deck.cards)?;
+        }
+        if let Some(deck) = decks.iter().find(|deck| deck.len() < needed) {
+            return Err(anyhow!(
+                "{} cards to deal from, {needed} needed",
+                deck.len()
+            ));
+        }
+        if dealer >= variant.players() {
+            return Err(anyhow!("no player {dealer} to deal"));
+        }
+        Ok(Self::dealing(variant, rules, seed, dealer, decks.into()))
+    }
+
+    fn dealing(
+        variant: Variant,
+        rules: RuleSet,
+        seed: u64,
+        dealer: usize,
+        stacked: VecDeque<Deck>,
+    ) -> Self {
+        let players = variant.players();
+        let mut record = GameRecord::new(seed, dealer);
+        record.variant = variant;
+        record.rules = rules;
+
+        let mut game = Self {
+            rng: StdRng::seed_from_u64(seed),
+            deck: Deck { cards: Vec::new() },
+            stacked,
+            variant,
+            rules,
+            board: Board::with_target(variant.teams(), rules.game)
+                .with_objective(variant.objective()),
+            dealer,
+            crib_owner: dealer,
+            bidder: dealer,
+            high_bid: None,
+            hands: vec![Vec::new(); players],
+            discards: vec![Vec::new(); players],
+            crib: Vec::with_capacity(4),
+            starter: None,
+            pegging: None,
+            phase: Phase::Discard,
+            counts: VecDeque::new(),
+            record,
+        };
+        game.deal();
+        let pone = game.pone();
+        game.score(
+            &mut Vec::new(),
+            pone,
+            variant.head_start(),
+            Reason::ThreeForLast,
+        );
+        game
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn players(&self) -> usize {
+        self.variant.players()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    /// Player on the dealer's left, who leads the play and counts first unless they won the crib
+    /// at auction.
+    pub fn pone(&self) -> usize {
+        (self.dealer + 1) % self.players()
+    }
+
+    /// Player whose crib it is: the dealer, or in auction games the winner of the auction. They
+    /// take the dealer's part in the play and the show, but his heels is still the dealer's.
+    pub fn crib_owner(&self) -> usize {
+        self.crib_owner
+    }
+
+    /// Player who must bid or pass during the auction.
+    pub fn to_bid(&self) -> Option<usize> {
+        Some(self.bidder).filter(|_| self.phase == Phase::Bid)
+    }
+
+    /// Highest bid of the auction under way, and who made it.
+    pub fn high_bid(&self) -> Option<(usize, u8)> {
+        self.high_bid
+    }
+
+    /// Score of each team, one per player outside partnership games.
+    pub fn scores(&self) -> &[u8] {
+        self.board.scores()
+    }
+
+    /// Every team's pegs.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Every points scored in the game so far, in order.
+    pub fn log(&self) -> impl Iterator<Item = &Points> + '_ {
+        self.record.points()
+    }
+
+    /// Everything that happened in the game so far, to save or replay it.
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /// The team who won once the game is over, the player outside partnership games: the one who
+    /// reached the target, or in lowball the other.
+    pub fn winner(&self) -> Option<usize> {
+        self.board.winner()
+    }
+
+    /// Cards held by `player`: as dealt before the discard, then the ones kept for the show.
+    pub fn hand(&self, player: usize) -> &[Card] {
+        &self.hands[player]
+    }
+
+    pub fn has_discarded(&self, player: usize) -> bool {
+        !self.discards[player].is_empty()
+    }
+
+    pub fn discards(&self, player: usize) -> &[Card] {
+        &self.discards[player]
+    }
+
+    pub fn crib(&self) -> &[Card] {
+        &self.crib
+    }
+
+    pub fn starter(&self) -> Option<Card> {
+        self.starter
+    }
+
+    pub fn pegging(&self) -> Option<&Pegging> {
+        self.pegging.as_ref()
+    }
+
+    /// Player who must count next when playing muggins, and whether it's the crib.
+    pub fn to_count(&self) -> Option<(usize, bool)> {
+        self.counts
+            .front()
+            .copied()
+            .filter(|_| self.phase == Phase::Count)
+    }
+
+    /// What `to_count` must count: their hand or the crib, the starter and the rules to count
+    /// them by.
+    pub fn counting(&self) -> Option<(&[Card], Card, ShowRules)> {
+        let (player, crib) = self.to_count()?;
+        let cards = if crib {
+            &self.crib
+        } else {
+            &self.hands[player]
+        };
+        Some((cards, self.starter?, self.rules.show(crib)))
+    }
+
+    /// What `player` knows of the game when bidding for the crib.
+    pub fn bid_state(&self, player: usize) -> BidState<'_> {
+        BidState::new(
+            &self.hands[player],
+            self.variant.discards(),
+            self.high_bid.map(|(_, bid)| bid),
+            self.dealer == player,
+            self.scores_for(player),
+        )
+    }
+
+    /// What `player` knows of the game when throwing to the crib.
+    pub fn discard_state(&self, player: usize) -> DiscardState<'_> {
+        DiscardState::new(
+            &self.hands[player],
+            self.variant.discards(),
+            self.dealer == player,
+            self.variant.team(player) == self.variant.team(self.crib_owner),
+            self.scores_for(player),
+            self.players(),
+        )
+        .with_objective(self.variant.objective())
+        .with_target(self.rules.game)
+    }
+
+    /// What `player` knows of the game during the play.
+    pub fn pegging_state(&self, player: usize) -> Option<PeggingState<'_>> {
+        let pegging = self
+            .pegging
+            .as_ref()
+            .filter(|_| self.phase == Phase::Pegging)?;
+
+        Some(
+            PeggingState::new(
+                player,
+                pegging,
+                self.starter?,
+                &self.discards[player],
+                self.dealer == player,
+                self.scores_for(player),
+            )
+            .with_objective(self.variant.objective())
+            .with_teams(self.variant.teams())
+            .with_rules(self.rules),
+        )
+    }
+
+    /// Scores from `player`'s point of view, their team's against the leading opponent's.
+    pub fn scores_for(&self, player: usize) -> Scores {
+        let team = self.variant.team(player);
+        Scores {
+            own: self.board.score(team),
+            opponent: (0..self.variant.teams())
+                .filter(|other| *other != team)
+                .map(|other| self.board.score(other))
+                .max()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Bid `bid` points for the crib, or pass with `None`, in auction games. The pone bids first.
+    ///
+    /// Bids must top the last one, up to `MAX_BID`. A pass ends the auction: the last bidder pegs
+    /// their bid back and takes the crib, or the other player takes it for nothing when the pone
+    /// passes first. Bids past the bidder's score are owed, and paid off by the next points.
+    pub fn bid(&mut self, player: usize, bid: Option<u8>) -> Result<()> {
+        let Some(bidder) = self.to_bid() else {
+            return Err(anyhow!("not time to bid"));
+        };
+        if player != bidder {
+            return Err(anyhow!("not player {player}'s turn to bid"));
+        }
+        if let (Some(bid), Some((_, high))) = (bid, self.high_bid) {
+            if bid <= high {
+                return Err(anyhow!("player {player} must bid more than {high}"));
+            }
+        }
+        if bid.is_some_and(|bid| bid > MAX_BID) {
+            return Err(anyhow!(
+                "bids go up to {MAX_BID}, what a crib scores at most"
+            ));
+        }
+
+        self.record.push(Event::Bid { player, bid });
+        let other = (player + 1) % self.players();
+        match bid {
+            Some(bid) => {
+                self.high_bid = Some((player, bid));
+                self.bidder = other;
+            }
+            None => {
+                let (owner, price) = self.high_bid.unwrap_or((other, 0));
+                self.board.pay(self.variant.team(owner), price)?;
+                self.crib_owner = owner;
+                self.phase = Phase::Discard;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Throw `cards` to the crib. Once every player did, the starter is cut.
+    pub fn discard(&mut self, player: usize, cards: &[Card]) -> Result<Vec<Points>> {
+        if self.phase != Phase::Discard {
+            return Err(anyhow!("not time to discard"));
+        }
+        if player >= self.players() {
+            return Err(anyhow!("no player {player}"));
+        }
+        if self.has_discarded(player) {
+            return Err(anyhow!("player {player} already discarded"));
+        }
+        if cards.len() != self.variant.discards()
+            || !cards.iter().all_unique()
+            || !cards.iter().all(|card| self.hands[player].contains(card))
+        {
+            return Err(anyhow!(
+                "player {player} can't discard {}, {} cards of their hand expected",
+                CardStyle::Ascii.cards(cards),
+                self.variant.discards()
+            ));
+        }
+
+        self.hands[player].retain(|card| !cards.contains(card));
+        self.record.push(Event::Discard {
+            player,
+            cards: cards.to_vec(),
+        });
+        self.crib.extend(cards);
+        self.discards[player].extend(cards);
+
+        let mut points = Vec::new();
+        if self.discards.iter().all(|discards| !discards.is_empty()) {
+            self.cut(&mut points);
+        }
+
+        Ok(points)
+    }
+
+    /// Play a card or say go during the play. The show is counted when the last card is played.
+    pub fn peg(&mut self, player: usize, action: PegAction) -> Result<Vec<Points>> {
+        let Some(pegging) = self.pegging.as_mut() else {
+            return Err(anyhow!("not time to play"));
+        };
+        if self.phase != Phase::Pegging {
+            return Err(anyhow!("not time to play"));
+        }
+
+        let pegs = pegging.act(player, action)?;
+        let over = pegging.is_over();
+        self.record.push(Event::Peg { player, action });
+
+        let mut points = Vec::new();
+        for (player, peg) in pegs {
+            let n = self.rules.peg_points(peg);
+            self.score(&mut points, player, n, Reason::Peg(peg));
+        }
+
+        if self.phase == Phase::Pegging && over {
+            self.show(&mut points)?;
+        }
+
+        Ok(points)
+    }
+
+    /// Pass the deal to the next player and deal a new hand.
+    pub fn next_hand(&mut self) -> Result<()> {
+        if self.phase != Phase::Show {
+            return Err(anyhow!("the hand is not over"));
+        }
+
+        self.dealer = self.pone();
+        self.deal();
+        Ok(())
+    }
+
+    fn deal(&mut self) {
+        self.deck = match self.stacked.pop_front() {
+            Some(deck) => deck,
+            None => Deck::new_shuffled_with(&mut self.rng),
+        };
+        self.record.push(Event::Hand {
+            dealer: self.dealer,
+        });
+        for (player, hand) in self.hands.iter_mut().enumerate() {
+            hand.clear();
+            hand.extend((0..self.variant.dealt()).map(|_| self.deck.draw()));
+            hand.sort();
+            self.record.push(Event::Deal {
+                player,
+                cards: hand.clone(),
+            });
+        }
+
+        self.discards.iter_mut().for_each(Vec::clear);
+        self.crib.clear();
+        for _ in 0..self.variant.crib_from_deck() {
+            let card = self.deck.draw();
+            self.crib.push(card);
+            self.record.push(Event::CribCard(card));
+        }
+        self.starter = None;
+        self.pegging = None;
+        self.crib_owner = self.dealer;
+        self.bidder = self.pone();
+        self.high_bid = None;
+        self.phase = match self.variant.has_auction() {
+            true => Phase::Bid,
+            false => Phase::Discard,
+        };
+    }
+
+    fn cut(&mut self, points: &mut Vec<Points>) {
+        let starter = self.deck.draw();
+        self.starter = Some(starter);
+        self.record.push(Event::Starter(starter));
+
+        self.phase = Phase::Pegging;
+        let leader = (self.crib_owner + 1) % self.players();
+        let pegging = Pegging::new(self.hands.clone(), leader).expect("no jokers dealt");
+        self.pegging = Some(pegging);
+
+        if starter.number() == Number::J {
+            self.score(points, self.dealer, self.rules.his_heels, Reason::HisHeels);
+        }
+    }
+
+    /// Claim `claimed` points for the hand or crib `player` must count when playing muggins.
+    ///
+    /// Points claimed are scored up to the actual count: the ones missed go to the next
+    /// opponent.
+    pub fn count(&mut self, player: usize, claimed: u8) -> Result<Vec<Points>> {
+        let Some((counting, crib)) = self.to_count() else {
+            return Err(anyhow!("not time to count"));
+        };
+        if player != counting {
+            return Err(anyhow!("not player {player}'s turn to count"));
+        }
+
+        self.counts.pop_front();
+        self.record.push(Event::Count {
+            player,
+            points: claimed,
+        });
+
+        let actual = self.show_score(player, crib)?;
+        let reason = if crib { Reason::Crib } else { Reason::Hand };
+        let mut points = Vec::new();
+        self.score(&mut points, player, claimed.min(actual), reason);
+
+        let players = self.players();
+        let opponent = (1..players)
+            .map(|offset| (player + offset) % players)
+            .find(|other| self.variant.team(*other) != self.variant.team(player))
+            .unwrap_or(player);
+        let missed = actual.saturating_sub(claimed);
+        self.score(&mut points, opponent, missed, Reason::Muggins);
+
+        if self.phase == Phase::Count && self.counts.is_empty() {
+            self.phase = Phase::Show;
+        }
+
+        Ok(points)
+    }
+
+    /// Count the hands from the crib owner's left, then the owner's hand and crib: the dealer's,
+    /// unless auctioned. Playing muggins, wait for each player to count instead.
+    fn show(&mut self, points: &mut Vec<Points>) -> Result<()> {
+        let (players, owner) = (self.players(), self.crib_owner);
+        let mut counts = (1..=players)
+            .map(|offset| ((owner + offset) % players, false))
+            .collect::<VecDeque<_>>();
+        counts.push_back((owner, true));
+
+        if self.rules.muggins {
+            self.phase = Phase::Count;
+            self.counts = counts;
+            return Ok(());
+        }
+
+        self.phase = Phase::Show;
+        for (player, crib) in counts {
+            let score = self.show_score(player, crib)?;
+            let reason = if crib { Reason::Crib } else { Reason::Hand };
+            self.score(points, player, score, reason);
+        }
+
+        Ok(())
+    }
+
+    /// Actual score of `player`'s hand, or of the crib.
+    fn show_score(&self, player: usize, crib: bool) -> Result<u8> {
+        let starter = self.starter.ok_or_else(|| anyhow!("no starter"))?;
+        let cards = if crib {
+            &self.crib
+        } else {
+            &self.hands[player]
+        };
+        Ok(score_kept(cards, starter, self.rules.show(crib)))
+    }
+
+    fn score(&mut self, points: &mut Vec<Points>, player: usize, n: u8, reason: Reason) {
+        if self.phase == Phase::Over || n == 0 {
+            return;
+        }
+
+        self.board
+            .peg(self.variant.team(player), n)
+            .expect("pegging before the game is won");
+        let scored = Points {
+            player,
+            points: n,
+            reason,
+        };
+        points.push(scored);
+        self.record.push(Event::Points(scored));
+
+        if self.board.winner().is_some() {
+            self.phase = Phase::Over;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Objective;
+
+    /// Discard the first cards and play the first playable card, or go.
+    fn play_first(game: &mut Game) -> Result<()> {
+        play_until(game, Phase::Over)
+    }
+
+    /// Play as `play_first` until `phase`, counting nothing when playing muggins.
+    fn play_until(game: &mut Game, phase: Phase) -> Result<()> {
+        loop {
+            if game.phase() == phase {
+                return Ok(());
+            }
+            match game.phase() {
+                Phase::Bid => {
+                    let player = game.to_bid().unwrap();
+                    game.bid(player, None)?;
+                }
+                Phase::Discard => {
+                    for player in 0..game.players() {
+                        let discard = game.hand(player)[..game.variant().discards()].to_vec();
+                        game.discard(player, &discard)?;
+                    }
+                }
+                Phase::Pegging => {
+                    let pegging = game.pegging().unwrap();
+                    let player = pegging.to_play().unwrap();
+                    let action = match pegging.playable(player).next() {
+                        Some(card) => PegAction::Play(card),
+                        None => PegAction::Go,
+                    };
+                    game.peg(player, action)?;
+                }
+                Phase::Count => {
+                    let (player, _) = game.to_count().unwrap();
+                    game.count(player, 0)?;
+                }
+                Phase::Show => game.next_hand()?,
+                Phase::Over => return Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn play_to_121() -> Result<()> {
+        let mut game = Game::new(42);
+        play_first(&mut game)?;
+
+        let winner = game.winner().unwrap();
+        assert_eq!(GAME_POINTS, game.scores()[winner]);
+        assert!(game.scores()[1 - winner] < GAME_POINTS);
+        assert_eq!(Some(winner), game.board().winner());
+        assert!(game.board().game_points().is_some());
+
+        // The log adds up to the scores, except for points past 121
+        let logged = |player| {
+            game.log()
+                .filter(|points| points.player == player)
+                .map(|points| points.points as u32)
+                .sum::<u32>()
+        };
+        assert!(logged(winner) >= GAME_POINTS as u32);
+        assert_eq!(game.scores()[1 - winner] as u32, logged(1 - winner));
+
+        Ok(())
+    }
+
+    #[test]
+    fn five_card_game() -> Result<()> {
+        let mut game = Game::with_variant(Variant::FiveCard, 5, 0);
+        assert_eq!(&[0, 3], game.scores());
+        assert_eq!(Reason::ThreeForLast, game.log().next().unwrap().reason);
+        assert!(game.hand(0).len() == 5 && game.crib().is_empty());
+
+        for player in 0..2 {
+            let discard = game.hand(player)[..2].to_vec();
+            game.discard(player, &discard)?;
+        }
+        assert_eq!(3, game.hand(1).len());
+        assert_eq!(4, game.crib().len());
+
+        play_first(&mut game)?;
+        let winner = game.winner().unwrap();
+        assert_eq!(61, game.scores()[winner]);
+        assert!(game.scores()[1 - winner] < 61);
+        Ok(())
+    }
+
+    #[test]
+    fn seven_card_game() -> Result<()> {
+        let mut game = Game::with_variant(Variant::SevenCard, 9, 1);
+        assert_eq!(7, game.hand(0).len());
+        assert_eq!(&[0, 0], game.scores());
+
+        for player in 0..2 {
+            let discard = game.hand(player)[..2].to_vec();
+            game.discard(player, &discard)?;
+        }
+        assert_eq!(5, game.hand(0).len());
+        assert_eq!(4, game.crib().len());
+
+        play_first(&mut game)?;
+        let winner = game.winner().unwrap();
+        assert_eq!(181, game.scores()[winner]);
+        assert!(game.scores()[1 - winner] < 181);
+        Ok(())
+    }
+
+    #[test]
+    fn auction() -> Result<()> {
+        let mut game = Game::with_variant(Variant::Auction, 3, 0);
+        assert_eq!(Phase::Bid, game.phase());
+        assert_eq!(Some(1), game.to_bid());
+        assert!(game.bid(0, Some(0)).is_err());
+        let discard = game.hand(1)[..2].to_vec();
+        assert!(game.discard(1, &discard).is_err());
+
+        game.bid(1, Some(0))?;
+        assert!(game.bid(0, Some(0)).is_err());
+        game.bid(0, None)?;
+        assert_eq!((0, 1), (game.dealer(), game.crib_owner()));
+        assert_eq!(Phase::Discard, game.phase());
+        play_until(&mut game, Phase::Pegging)?;
+        // The player who didn't win the crib leads
+        assert_eq!(Some(0), game.pegging().unwrap().to_play());
+
+        play_until(&mut game, Phase::Show)?;
+        game.next_hand()?;
+        assert_eq!(1, game.crib_owner());
+        let score = game.scores()[0];
+        assert!(score >= 2, "{score}");
+        game.bid(0, Some(2))?;
+        game.bid(1, None)?;
+        assert_eq!(score - 2, game.scores()[0]);
+        assert_eq!(0, game.crib_owner());
+
+        let mut game = Game::with_variant(Variant::Auction, 3, 0);
+        game.bid(1, None)?;
+        assert_eq!(0, game.crib_owner());
+
+        // Opening bids go past the score, owed until the bidder scores as much
+        let mut game = Game::with_variant(Variant::Auction, 3, 0);
+        assert!(game.bid(1, Some(MAX_BID + 1)).is_err());
+        game.bid(1, Some(3))?;
+        game.bid(0, Some(5))?;
+        game.bid(1, None)?;
+        assert_eq!(
+            (0, 0, 5),
+            (game.crib_owner(), game.scores()[0], game.board().debt(0))
+        );
+        play_until(&mut game, Phase::Show)?;
+        let pegged = game.record().points().filter(|points| points.player == 0);
+        let score = pegged
+            .map(|points| points.points)
+            .sum::<u8>()
+            .saturating_sub(5);
+        assert_eq!(score, game.scores()[0]);
+        assert_eq!(game.record().scores(), game.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn lowball_game() -> Result<()> {
+        let mut game = Game::with_variant(Variant::Lowball, 2, 0);
+        assert_eq!(Objective::Fewest, game.discard_state(0).objective());
+
+        play_first(&mut game)?;
+        let winner = game.winner().unwrap();
+        assert_eq!(GAME_POINTS, game.scores()[1 - winner]);
+        assert!(game.scores()[winner] < GAME_POINTS);
+        Ok(())
+    }
+
+    #[test]
+    fn rules_change_the_points() -> Result<()> {
+        let mut game = Game::with_rules(Variant::Standard, "heels=0 go=0".parse()?, 1, 0);
+        play_first(&mut game)?;
+        assert!(game
+            .log()
+            .all(|points| !matches!(points.reason, Reason::HisHeels | Reason::Peg(Peg::Go))));
+        assert!(game
+            .log()
+            .any(|points| points.reason == Reason::Peg(Peg::LastCard)));
+
+        let mut game = Game::with_rules(Variant::Standard, "game=61".parse()?, 1, 0);
+        play_first(&mut game)?;
+        assert_eq!(61, game.scores()[game.winner().unwrap()]);
+        Ok(())
+    }
+
+    #[test]
+    fn muggins() -> Result<()> {
+        let mut game = Game::with_rules(Variant::Standard, RuleSet::house(), 3, 0);
+        play_until(&mut game, Phase::Count)?;
+        assert_eq!(Some((1, false)), game.to_count());
+        assert!(game.count(0, 0).is_err());
+
+        // The pone counts nothing: the dealer takes it all
+        let (cards, starter, rules) = game.counting().unwrap();
+        let actual = crate::score_cards(cards, starter, rules)?;
+        let before = game.scores().to_vec();
+        let points = game.count(1, 0)?;
+        assert_eq!(before[1], game.scores()[1]);
+        assert_eq!(before[0] + actual, game.scores()[0]);
+        assert_eq!(
+            actual > 0,
+            points.iter().any(|p| p.reason == Reason::Muggins)
+        );
+
+        // Over-counting gets no more than the count
+        assert_eq!(Some((0, false)), game.to_count());
+        let (cards, starter, rules) = game.counting().unwrap();
+        let actual = crate::score_cards(cards, starter, rules)?;
+        let before = game.scores().to_vec();
+        game.count(0, 29)?;
+        assert_eq!(before[0] + actual, game.scores()[0]);
+
+        assert_eq!(Some((0, true)), game.to_count());
+        game.count(0, 0)?;
+        assert_eq!(Phase::Show, game.phase());
+        assert_eq!(None, game.to_count());
+        Ok(())
+    }
+
+    #[test]
+    fn same_seed_same_game() -> Result<()> {
+        let mut game1 = Game::new(7);
+        let mut game2 = Game::new(7);
+        assert_eq!(game1.hand(0), game2.hand(0));
+
+        play_first(&mut game1)?;
+        play_first(&mut game2)?;
+        assert_eq!(game1.scores(), game2.scores());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hand_flow() -> Result<()> {
+        let mut game = Game::with_dealer(1, 0);
+        assert_eq!(Phase::Discard, game.phase());
+        assert_eq!(1, game.pone());
+        assert_eq!(6, game.hand(0).len());
+
+        let hand = game.hand(0).to_vec();
+        assert!(game.discard(0, &[hand[0], hand[0]]).is_err());
+        game.discard(0, &[hand[0], hand[1]])?;
+        assert!(game.discard(0, &[hand[2], hand[3]]).is_err());
+        assert_eq!(Phase::Discard, game.phase());
+        assert!(game.peg(1, PegAction::Go).is_err());
+
+        let hand = game.hand(1).to_vec();
+        game.discard(1, &[hand[4], hand[5]])?;
+        assert_eq!(Phase::Pegging, game.phase());
+        assert_eq!(4, game.crib().len());
+        assert!(game.starter().is_some());
+        assert_eq!(Some(1), game.pegging().unwrap().to_play());
+        assert!(game.next_hand().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn three_player_hand() -> Result<()> {
+        let mut game = Game::with_variant(Variant::ThreePlayer, 3, 2);
+        assert_eq!(3, game.players());
+        assert_eq!(0, game.pone());
+        assert_eq!(1, game.crib().len());
+        for player in 0..3 {
+            assert_eq!(5, game.hand(player).len());
+            let hand = game.hand(player).to_vec();
+            assert!(game.discard(player, &hand[..2]).is_err());
+            game.discard(player, &hand[..1])?;
+        }
+        assert_eq!(4, game.crib().len());
+        assert_eq!(Some(0), game.pegging().unwrap().to_play());
+
+        // Hands are counted from the dealer's left: player 0, then 1, then the dealer and the crib
+        while game.phase() == Phase::Pegging {
+            let pegging = game.pegging().unwrap();
+            let player = pegging.to_play().unwrap();
+            let action = match pegging.playable(player).next() {
+                Some(card) => PegAction::Play(card),
+                None => PegAction::Go,
+            };
+            game.peg(player, action)?;
+        }
+        let show = game
+            .record()
+            .points()
+            .filter(|points| matches!(points.reason, Reason::Hand | Reason::Crib))
+            .map(|points| points.player)
+            .collect::<Vec<_>>();
+        assert!(show.windows(2).all(|pair| pair[0] <= pair[1]), "{show:?}");
+
+        game.next_hand()?;
+        assert_eq!(0, game.dealer());
+        Ok(())
+    }
+
+    #[test]
+    fn partners_share_a_board() -> Result<()> {
+        let mut game = Game::with_variant(Variant::Partnership, 5, 3);
+        assert_eq!(4, game.players());
+        assert_eq!(2, game.board().players());
+        assert!(game.discard_state(1).owns_crib());
+        assert!(!game.discard_state(1).is_dealer());
+        assert!(!game.discard_state(0).owns_crib());
+
+        play_first(&mut game)?;
+        let winner = game.winner().unwrap();
+        assert_eq!(2, game.scores().len());
+        assert_eq!(GAME_POINTS, game.scores()[winner]);
+
+        // Partners' points add up on their team's pegs
+        let logged = |team| {
+            game.log()
+                .filter(|points| points.player % 2 == team)
+                .map(|points| points.points as u32)
+                .sum::<u32>()
+        };
+        assert_eq!(game.scores()[1 - winner] as u32, logged(1 - winner));
+        assert_eq!(game.scores_for(0), game.scores_for(2));
+        Ok(())
+    }
+
+    #[test]
+    fn three_player_game() -> Result<()> {
+        let mut game = Game::new_variant(Variant::ThreePlayer, 11);
+        play_first(&mut game)?;
+
+        let winner = game.winner().unwrap();
+        assert_eq!(GAME_POINTS, game.scores()[winner]);
+        assert_eq!(3, game.scores().len());
+        assert_eq!(
+            1,
+            game.scores()
+                .iter()
+                .filter(|score| **score == GAME_POINTS)
+                .count()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::SeedableRng;
+
+use crate::{best_mean_discard, Card, DiscardState, PegAction, Pegging, PeggingState, Player};
+
+/// Pegging bot searching the rest of the play with information set Monte Carlo tree search
+/// (ISMCTS).
+///
+/// Each iteration deals the unseen cards to the opponent at random, consistent with what they
+/// played and the gos they said, then walks down a tree of actions shared by every guess. The
+/// play is finished at random and the points pegged by each team are backed up the tree, as
+/// penalties in lowball. The most visited first action wins. More iterations means a stronger,
+/// slower bot.
+///
+/// Discards keep the hand with the best mean, like `GreedyShow`.
+pub struct IsmctsPlayer {
+    iterations: u32,
+    exploration: f64,
+    rng: RefCell<StdRng>,
+}
+
+struct Node {
+    /// Action leading to this node, taken by `player`.
+    action: PegAction,
+    player: usize,
+    children: Vec<usize>,
+    visits: u32,
+    /// Number of times this node could have been picked, given the guessed cards.
+    available: u32,
+    /// Net points (own team's minus opponents', negated in lowball) pegged by `player`'s team from
+    /// this node on, summed over visits.
+    reward: f64,
+}
+
+impl IsmctsPlayer {
+    pub fn new(iterations: u32, seed: u64) -> Self {
+        Self {
+            iterations,
+            exploration: 4.0,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// UCB exploration constant, in points. Higher explores more.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Pick the action for the player of `state` to take.
+    pub fn search(&self, state: &PeggingState) -> PegAction {
+        let actions = state.playable().map(PegAction::Play).collect::<Vec<_>>();
+        match actions.len() {
+            0 => return PegAction::Go,
+            1 => return actions[0],
+            _ => {}
+        }
+
+        let sign = state.objective().sign();
+        let mut rng = self.rng.borrow_mut();
+        let rng = &mut *rng;
+        let mut tree = vec![Node {
+            action: PegAction::Go,
+            player: state.player(),
+            children: Vec::new(),
+            visits: 0,
+            available: 0,
+            reward: 0.0,
+        }];
+
+        for _ in 0..self.iterations {
+            let mut pegging = state.determinize(rng);
+            let mut points = vec![0i32; pegging.players()];
+            let mut path = vec![0];
+            let mut node = 0;
+
+            // Selection and expansion
+            while let Some(player) = pegging.to_play() {
+                let actions = legal_actions(&pegging, player);
+
+                let mut untried = Vec::new();
+                for action in &actions {
+                    let existing = tree[node]
+                        .children
+                        .iter()
+                        .copied()
+                        .find(|child| tree[*child].action == *action);
+                    match existing {
+                        Some(child) => tree[child].available += 1,
+                        None => untried.push(*action),
+                    }
+                }
+
+                let (child, expanded) = match untried.choose(rng) {
+                    Some(action) => {
+                        tree.push(Node {
+                            action: *action,
+                            player,
+                            children: Vec::new(),
+                            visits: 0,
+                            available: 1,
+                            reward: 0.0,
+                        });
+                        let child = tree.len() - 1;
+                        tree[node].children.push(child);
+                        (child, true)
+                    }
+                    None => (self.select(&tree, node, &actions), false),
+                };
+
+                apply(state, &mut pegging, player, tree[child].action, &mut points);
+                path.push(child);
+                node = child;
+                if expanded {
+                    break;
+                }
+            }
+
+            // Simulation
+            while let Some(player) = pegging.to_play() {
+                let action = legal_actions(&pegging, player)
+                    .into_iter()
+                    .choose(rng)
+                    .expect("at least go");
+                apply(state, &mut pegging, player, action, &mut points);
+            }
+
+            // Backpropagation
+            for node in path {
+                let node = &mut tree[node];
+                node.visits += 1;
+                node.reward += (net_points(state, node.player, &points) * sign) as f64;
+            }
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|child| tree[**child].visits)
+            .map(|child| tree[*child].action)
+            .unwrap_or(actions[0])
+    }
+
+    /// Child of `node` among the legal `actions` with the best upper confidence bound.
+    fn select(&self, tree: &[Node], node: usize, actions: &[PegAction]) -> usize {
+        tree[node]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| actions.contains(&tree[*child].action))
+            .max_by(|a, b| self.ucb(&tree[*a]).total_cmp(&self.ucb(&tree[*b])))
+            .expect("every legal action was tried")
+    }
+
+    fn ucb(&self, node: &Node) -> f64 {
+        let visits = node.visits.max(1) as f64;
+        node.reward / visits
+            + self.exploration * ((node.available.max(1) as f64).ln() / visits).sqrt()
+    }
+}
+
+impl Player for IsmctsPlayer {
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>> {
+        best_mean_discard(state.hand(), state.discards(), state.objective())
+    }
+
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>> {
+        Ok(match self.search(state) {
+            PegAction::Play(card) => Some(card),
+            PegAction::Go => None,
+        })
+    }
+}
+
+/// Points pegged by `player`'s team minus the other teams'.
+fn net_points(state: &PeggingState, player: usize, points: &[i32]) -> i32 {
+    let team = state.team(player);
+    points
+        .iter()
+        .enumerate()
+        .map(|(scorer, points)| match state.team(scorer) == team {
+            true => *points,
+            false => -*points,
+        })
+        .sum()
+}
+
+fn legal_actions(pegging: &Pegging, player: usize) -> Vec<PegAction> {
+    let actions = pegging
+        .playable(player)
+        .map(PegAction::Play)
+        .collect::<Vec<_>>();
+
+    match actions.is_empty() {
+        true => vec![PegAction::Go],
+        false => actions,
+    }
+}
+
+/// Take `action` in the guessed `pegging`, adding the points pegged by the rules of `state`.
+fn apply(
+    state: &PeggingState,
+    pegging: &mut Pegging,
+    player: usize,
+    action: PegAction,
+    points: &mut [i32],
+) {
+    let pegs = pegging.act(player, action).expect("legal action");
+    for (player, peg) in pegs {
+        points[player] += state.rules().peg_points(peg) as i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_cards, play_game, Game, GreedyShow, RuleSet, Scores};
+
+    fn cards(input: &str) -> Vec<Card> {
+        parse_cards(input).unwrap()
+    }
+
+    fn state(pegging: &Pegging) -> PeggingState<'_> {
+        PeggingState::new(1, pegging, cards("Kc")[0], &[], false, Scores::default())
+    }
+
+    #[test]
+    fn takes_the_fifteen() {
+        let mut pegging = Pegging::new(vec![cards("Th 2c 3d"), cards("5s 9d 2h")], 0).unwrap();
+        pegging.act(0, PegAction::Play(cards("Th")[0])).unwrap();
+
+        let bot = IsmctsPlayer::new(500, 1);
+        assert_eq!(Some(cards("5s")[0]), bot.play(&state(&pegging)).unwrap());
+    }
+
+    #[test]
+    fn goes_when_stuck() {
+        let mut pegging = Pegging::new(vec![cards("Th Jd Qc"), cards("Ks 9d")], 0).unwrap();
+        pegging.act(0, PegAction::Play(cards("Th")[0])).unwrap();
+        pegging.act(1, PegAction::Play(cards("Ks")[0])).unwrap();
+        pegging.act(0, PegAction::Play(cards("Jd")[0])).unwrap();
+
+        let bot = IsmctsPlayer::new(100, 1);
+        assert_eq!(None, bot.play(&state(&pegging)).unwrap());
+    }
+
+    #[test]
+    fn pegs_by_the_rules() {
+        let mut pegging = Pegging::new(vec![cards("Th Jd Qc"), cards("Ks 9d")], 0).unwrap();
+        pegging.act(0, PegAction::Play(cards("Th")[0])).unwrap();
+        pegging.act(1, PegAction::Play(cards("Ks")[0])).unwrap();
+        pegging.act(0, PegAction::Play(cards("Jd")[0])).unwrap();
+
+        // Count is 30: nobody can play, and player 0 pegs the go if it scores
+        let no_go = RuleSet {
+            go: 0,
+            ..RuleSet::standard()
+        };
+        for (rules, go) in [(RuleSet::standard(), 1), (no_go, 0)] {
+            let state = state(&pegging).with_rules(rules);
+            let mut guess = pegging.clone();
+            let mut points = [0; 2];
+            apply(&state, &mut guess, 1, PegAction::Go, &mut points);
+            apply(&state, &mut guess, 0, PegAction::Go, &mut points);
+            assert_eq!([go, 0], points);
+        }
+    }
+
+    #[test]
+    fn guesses_respect_gos() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut pegging = Pegging::new(vec![cards("Th Jd"), cards("Ks 9d 8h")], 1).unwrap();
+        pegging.act(1, PegAction::Play(cards("Ks")[0])).unwrap();
+        pegging.act(0, PegAction::Play(cards("Th")[0])).unwrap();
+        pegging.act(1, PegAction::Play(cards("9d")[0])).unwrap();
+        // Count is 29: player 0 says go, so their last card is higher than a 2
+        pegging.act(0, PegAction::Go).unwrap();
+
+        let state = state(&pegging);
+        for _ in 0..50 {
+            let guess = state.determinize(&mut rng);
+            assert_eq!(1, guess.hand(0).len());
+            assert!(guess.hand(0)[0].value() > 2);
+        }
+    }
+
+    #[test]
+    fn partners_peg_for_each_other() {
+        let pegging =
+            Pegging::new(vec![cards("Th"), cards("Jd"), cards("Qc"), cards("Ks")], 0).unwrap();
+        let state = PeggingState::new(0, &pegging, cards("Kc")[0], &[], false, Scores::default());
+        let points = [0, 2, 5, 1];
+        assert_eq!(-8, net_points(&state, 0, &points));
+
+        let partners = state.with_teams(2);
+        assert_eq!(5 - 3, net_points(&partners, 0, &points));
+        assert_eq!(3 - 5, net_points(&partners, 3, &points));
+    }
+
+    #[test]
+    fn plays_full_games() {
+        let bot = IsmctsPlayer::new(50, 3);
+        let mut game = Game::new(11);
+        let winner = play_game(&mut game, &[&bot, &GreedyShow]).unwrap();
+        assert_eq!(Some(winner), game.winner());
+    }
+}
@@ -1,23 +1,97 @@
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use std::fmt::Display;
 use strum::VariantArray;
 
+mod analysis;
+mod board;
+mod bot;
+mod discard;
+mod endgame;
+mod engine;
+mod game;
+mod ismcts;
+mod matchplay;
+mod pegging;
+mod player;
+mod record;
+mod render;
+mod rules;
+mod show;
+mod squares;
+mod tournament;
+mod variant;
+
+pub use analysis::{Analysis, Choice, Decision};
+pub use board::{Board, Skunk, DOUBLE_SKUNK_LINE, SKUNK_LINE};
+pub use bot::{best_mean_discard, greedy_play, Bot, GreedyPegging, GreedyShow, RandomPlayer};
+pub use discard::{crib_mean, crib_worth, DiscardRanking, Keep, StarterScores};
+pub use endgame::{Position, RaceModel, ScoreDistributions, WinModel, WinTable};
+pub use engine::{Engine, EnginePlayer};
+pub use game::{Game, Phase, Points, Reason, GAME_POINTS, MAX_BID};
+pub use ismcts::IsmctsPlayer;
+pub use matchplay::{Match, MatchGame, MatchLength};
+pub use pegging::{score_play, Peg, PegAction, Pegging};
+pub use player::{play_game, BidState, DiscardState, PeggingState, Player, Scores};
+pub use record::{replay, Event, GameRecord};
+pub use render::{CardStyle, StyledCard};
+pub use rules::RuleSet;
+use show::score_show;
+pub use show::{assign_jokers, score_cards, ShowRules};
+pub use squares::{advise, Grid, Placement, Squares, SQUARES};
+pub use tournament::{new_player, Pairing, Standing, Tournament, TournamentResult, PLAYER_NAMES};
+pub use variant::{Objective, Variant};
+
 pub struct Deck {
     cards: Vec<Card>,
 }
 
 impl Deck {
     pub fn new_shuffled() -> Self {
-        let mut cards = Vec::with_capacity(Suit::VARIANTS.len() * Number::VARIANTS.len());
+        Self::new_shuffled_with(&mut thread_rng())
+    }
+
+    /// Shuffle with the given `rng`, e.g. a seeded one for reproducible games.
+    pub fn new_shuffled_with(rng: &mut impl Rng) -> Self {
+        Self::shuffled(&[], rng)
+    }
+
+    /// A deck drawing `top` first, in order, then the other cards of a 52-card deck: to deal
+    /// cards played away from the engine, e.g. at a club.
+    pub fn stacked(top: &[Card]) -> Self {
+        let mut cards = Suit::VARIANTS
+            .iter()
+            .flat_map(|suit| {
+                Number::VARIANTS
+                    .iter()
+                    .map(|number| Card::new(*number, *suit))
+            })
+            .filter(|card| !top.contains(card))
+            .collect::<Vec<_>>();
+        cards.extend(top.iter().rev());
+
+        Self { cards }
+    }
+
+    /// A deck of 52 cards plus 1 or 2 jokers, shuffled with `rng`.
+    pub fn with_jokers(jokers: usize, rng: &mut impl Rng) -> Result<Self> {
+        let jokers = Card::JOKERS
+            .get(..jokers)
+            .ok_or_else(|| anyhow!("at most {} jokers, not {jokers}", Card::JOKERS.len()))?;
+        Ok(Self::shuffled(jokers, rng))
+    }
+
+    fn shuffled(jokers: &[Card], rng: &mut impl Rng) -> Self {
+        let mut cards = Vec::with_capacity(Suit::VARIANTS.len() * Number::VARIANTS.len() + 2);
         for suit in Suit::VARIANTS.iter() {
             for number in Number::VARIANTS.iter() {
                 cards.push(Card::new(*number, *suit))
             }
         }
-        cards.shuffle(&mut thread_rng());
+        cards.extend_from_slice(jokers);
+        cards.shuffle(rng);
 
         Self { cards }
     }
@@ -26,6 +100,14 @@ impl Deck {
         self.cards.shuffle(&mut thread_rng());
     }
 
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     pub fn cards(&self) -> impl ExactSizeIterator<Item = &Card> {
         self.cards.iter()
     }
@@ -101,27 +183,48 @@ impl Display for Number {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Upper 4 bits of a joker, past every `Number`: jokers sort after the other cards.
+const JOKER: u8 = 0xF;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Card {
-    // Bit-packed Number and Suit. 4 high bits are number, lower 4 bits are suit.
+    // Bit-packed Number and Suit. 4 high bits are number, lower 4 bits are suit. Jokers have
+    // `JOKER` as number and tell each other apart by the lower bits.
     inner: u8,
 }
 
 impl Card {
+    /// The jokers a deck can hold, standing for any card in the show.
+    pub const JOKERS: [Card; 2] = [
+        Card { inner: JOKER << 4 },
+        Card {
+            inner: JOKER << 4 | 1,
+        },
+    ];
+
     pub fn new(number: Number, suit: Suit) -> Self {
         let inner = (number as u8) << 4 | (suit as u8);
         Self { inner }
     }
 
+    pub fn is_joker(&self) -> bool {
+        self.inner >> 4 == JOKER
+    }
+
+    /// Panics for a joker, which has no number until it's assigned a card: cards that aren't
+    /// going to the show are checked with `no_jokers` first.
     pub fn number(&self) -> Number {
-        // SAFETY: inner can only be constructed from a Number casted as u8 in the upper 4 bits;
-        // so it's safe to extract it.
+        assert!(!self.is_joker(), "a joker has no number");
+        // SAFETY: inner can only be constructed from a Number casted as u8 in the upper 4 bits,
+        // or be a joker, ruled out above; so it's safe to extract it.
         unsafe { std::mem::transmute(self.inner >> 4) }
     }
 
+    /// Panics for a joker, which has no suit until it's assigned a card.
     pub fn suit(&self) -> Suit {
+        assert!(!self.is_joker(), "a joker has no suit");
         // SAFETY: inner can only be constructed from a Suit casted as u8 in the lower 4 bits,
-        // so it's safe to extract it.
+        // or be a joker, ruled out above, so it's safe to extract it.
         unsafe { std::mem::transmute(self.inner & 0x0F) }
     }
 
@@ -130,9 +233,24 @@ impl Card {
     }
 }
 
+/// Error when `cards` hold a joker: jokers only stand for cards in the show, they can't be dealt
+/// in a game, played or placed.
+pub(crate) fn no_jokers(cards: &[Card]) -> Result<()> {
+    match cards.iter().any(Card::is_joker) {
+        true => Err(anyhow!(
+            "jokers only count in the show: {}",
+            CardStyle::Ascii.cards(cards)
+        )),
+        false => Ok(()),
+    }
+}
+
 impl Display for Card {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}", self.number(), self.suit())
+        match self.is_joker() {
+            true => write!(f, "Jk"),
+            false => write!(f, "{}{}", self.number(), self.suit()),
+        }
     }
 }
 
@@ -159,6 +277,7 @@ impl Hand {
     }
 
     pub fn from_slice(slice: &[Card]) -> Result<Self> {
+        no_jokers(slice)?;
         Ok(Self {
             cards: slice
                 .try_into()
@@ -166,15 +285,19 @@ impl Hand {
         })
     }
 
+    pub fn cards(&self) -> &[Card; 4] {
+        &self.cards
+    }
+
     pub fn score(&self, starter: Card, crib: bool) -> u8 {
         let cards4 = &self.cards;
         let mut cards5: [Card; 5] = [cards4[0], cards4[1], cards4[2], cards4[3], starter];
         cards5.sort();
 
         self.score_suit(starter, crib)
-            + self.score_fifteens(&cards5)
-            + self.score_pairs(&cards5)
-            + self.score_runs(&cards5)
+            + score_fifteens(&cards5)
+            + score_pairs(&cards5)
+            + score_runs(&cards5)
             + self.score_knob(starter)
     }
 
@@ -195,97 +318,113 @@ impl Hand {
         }
     }
 
-    fn score_fifteens(&self, cards5: &[Card; 5]) -> u8 {
-        let mut fifteens = 0;
+    pub fn score_knob(&self, starter: Card) -> u8 {
+        let knob = Card::new(Number::J, starter.suit());
+        self.cards.iter().contains(&knob) as u8
+    }
+}
 
-        for i in 0..cards5.len() {
-            for j in i + 1..cards5.len() {
-                let sum2 = cards5[i].value() + cards5[j].value();
-                if sum2 == 15 {
-                    fifteens += 1
-                }
+/// Score of a 3-card hand of five-card cribbage with its `starter`.
+///
+/// The 3 cards of a flush score 3, 4 with the starter.
+pub fn score_three_cards(cards: &[Card; 3], starter: Card) -> u8 {
+    score_show(cards, starter, ShowRules::HAND)
+}
 
-                for k in j + 1..cards5.len() {
-                    let sum3 = sum2 + cards5[k].value();
-                    if sum3 == 15 {
-                        fifteens += 1
-                    }
+/// Score of the cards kept for the show, from 3 to 5 of them. 4-card hands under standard rules
+/// take the faster `Hand::score`, jokers the best of every card they can stand for.
+pub(crate) fn score_kept(cards: &[Card], starter: Card, rules: ShowRules) -> u8 {
+    if starter.is_joker() || cards.iter().any(Card::is_joker) {
+        return assign_jokers(cards, starter, rules).0;
+    }
 
-                    for l in k + 1..cards5.len() {
-                        let sum4 = sum3 + cards5[l].value();
-                        // 4 cards
-                        if sum4 == 15 {
-                            fifteens += 1;
-                        }
-                    }
-                }
-            }
-        }
+    match Hand::from_slice(cards) {
+        Ok(hand) if rules.is_standard() => hand.score(starter, rules.crib),
+        _ => score_show(cards, starter, rules),
+    }
+}
 
-        if cards5.iter().map(Card::value).sum::<u8>() == 15 {
-            fifteens += 1;
-        }
+fn score_fifteens(cards: &[Card; 5]) -> u8 {
+    let mut fifteens = 0;
 
-        fifteens * 2
-    }
+    for i in 0..cards.len() {
+        for j in i + 1..cards.len() {
+            let sum2 = cards[i].value() + cards[j].value();
+            if sum2 == 15 {
+                fifteens += 1
+            }
 
-    fn score_pairs(&self, cards5: &[Card; 5]) -> u8 {
-        let mut pairs = 0u8;
+            for k in j + 1..cards.len() {
+                let sum3 = sum2 + cards[k].value();
+                if sum3 == 15 {
+                    fifteens += 1
+                }
 
-        for i in 0..cards5.len() {
-            let i_num = cards5[i].number();
-            for j in i + 1..cards5.len() {
-                if i_num == cards5[j].number() {
-                    pairs += 1
+                for card_l in &cards[k + 1..] {
+                    let sum4 = sum3 + card_l.value();
+                    // 4 cards
+                    if sum4 == 15 {
+                        fifteens += 1;
+                    }
                 }
             }
         }
+    }
 
-        pairs * 2
+    if cards.iter().map(Card::value).sum::<u8>() == 15 {
+        fifteens += 1;
     }
 
-    fn score_runs(&self, cards5: &[Card; 5]) -> u8 {
-        let mut run_start = cards5[0].number();
-        let mut run_length: u8 = 1;
+    fifteens * 2
+}
 
-        for i in 1..cards5.len() {
-            let c1 = cards5[i - 1].number();
-            let c2 = cards5[i].number();
+fn score_pairs(cards: &[Card; 5]) -> u8 {
+    let mut pairs = 0u8;
 
-            if c1 as u8 + 1 == c2 as u8 {
-                run_length += 1;
-            } else if c1 == c2 {
-                // Pair: no-op
-            } else if run_length >= 3 {
-                break;
-            } else {
-                run_start = c2;
-                run_length = 1;
+    for i in 0..cards.len() {
+        let i_num = cards[i].number();
+        for card_j in &cards[i + 1..] {
+            if i_num == card_j.number() {
+                pairs += 1
             }
         }
+    }
 
-        if run_length < 3 {
-            return 0;
-        }
+    pairs * 2
+}
 
-        let mut count_by_numbers = [0u8; Number::VARIANTS.len()];
-        for card in cards5.iter() {
-            count_by_numbers[card.number() as usize] += 1;
-        }
+fn score_runs(cards: &[Card; 5]) -> u8 {
+    let mut run_start = cards[0].number();
+    let mut run_length: u8 = 1;
 
-        let number_range_idx = run_start as usize..run_start as usize + run_length as usize;
+    for i in 1..cards.len() {
+        let c1 = cards[i - 1].number();
+        let c2 = cards[i].number();
 
-        run_length
-            * count_by_numbers[number_range_idx]
-                .iter()
-                .copied()
-                .fold(1, |memo, count| memo * count) as u8
+        if c1 as u8 + 1 == c2 as u8 {
+            run_length += 1;
+        } else if c1 == c2 {
+            // Pair: no-op
+        } else if run_length >= 3 {
+            break;
+        } else {
+            run_start = c2;
+            run_length = 1;
+        }
     }
 
-    pub fn score_knob(&self, starter: Card) -> u8 {
-        let knob = Card::new(Number::J, starter.suit());
-        self.cards.iter().contains(&knob) as u8
+    if run_length < 3 {
+        return 0;
+    }
+
+    let mut count_by_numbers = [0u8; Number::VARIANTS.len()];
+    for card in cards.iter() {
+        count_by_numbers[card.number() as usize] += 1;
     }
+
+    let number_range_idx = run_start as usize..run_start as usize + run_length as usize;
+
+    run_length * count_by_numbers[number_range_idx].iter().product::<u8>()
 }
 
 impl Display for Hand {
@@ -297,34 +436,65 @@ impl Display for Hand {
 
 impl Display for Suit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Suit::S => write!(f, "♠"),
-            Suit::H => write!(f, "♥"),
-            Suit::D => write!(f, "♦"),
-            Suit::C => write!(f, "♣"),
-        }
+        write!(f, "{}", CardStyle::Unicode.suit(*self))
+    }
+}
+
+/// Error returned by `parse_cards`, pointing at the offending character.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Position (in chars) of the offending character in the input.
+    pub position: usize,
+    message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
     }
 }
 
+impl std::error::Error for ParseError {}
+
+/// Parse a list of cards, e.g. "5d 6h Ac". Jokers are written `Jk`: the first one of the input
+/// is `Card::JOKERS[0]`, the second `Card::JOKERS[1]`.
+///
+/// On failure, the error can be downcast to a `ParseError` to find where the input is invalid.
 pub fn parse_cards(input: &str) -> Result<Vec<Card>> {
-    let mut iter = input.chars();
+    let mut iter = input.chars().enumerate();
     let mut cards: Vec<Card> = Vec::with_capacity(6);
 
+    let at = |position: usize| {
+        move |err: anyhow::Error| ParseError {
+            position,
+            message: err.to_string(),
+        }
+    };
+
     loop {
         match iter.next() {
-            Some(char) if char.is_whitespace() => continue,
-            Some(number_char) => {
-                let number: Number = number_char.try_into()?;
-                let suit = match iter.next() {
-                    Some(suit) => suit.try_into()?,
-                    None => {
-                        return Err(anyhow!(
-                            "unexpected end, missing suit after {}",
-                            number_char
-                        ))
+            Some((_, char)) if char.is_whitespace() => continue,
+            Some((number_pos, number_char)) => {
+                let number: Number = number_char.try_into().map_err(at(number_pos))?;
+                let Some((suit_pos, suit_char)) = iter.next() else {
+                    return Err(ParseError {
+                        position: number_pos + 1,
+                        message: format!("unexpected end, missing suit after {number_char}"),
                     }
+                    .into());
                 };
 
+                if number == Number::J && suit_char.eq_ignore_ascii_case(&'k') {
+                    let jokers = cards.iter().filter(|card| card.is_joker()).count();
+                    let joker = Card::JOKERS.get(jokers).ok_or_else(|| ParseError {
+                        position: number_pos,
+                        message: format!("at most {} jokers", Card::JOKERS.len()),
+                    })?;
+                    cards.push(*joker);
+                    continue;
+                }
+
+                let suit = suit_char.try_into().map_err(at(suit_pos))?;
                 cards.push(Card::new(number, suit))
             }
             None => break, // we're done!
@@ -392,6 +562,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn jokers() -> Result<()> {
+        let [first, second] = Card::JOKERS;
+        assert!(first.is_joker() && second.is_joker() && first != second);
+        assert!(!Card::new(Number::K, Suit::C).is_joker());
+        assert!(Card::new(Number::K, Suit::C) < first);
+
+        assert_eq!(
+            vec![first, Card::new(Number::J, Suit::C), second],
+            parse_cards("Jk Jc jK")?
+        );
+        assert_eq!("Jk", first.to_string());
+        assert!(parse_cards("Jk Jk Jk").is_err());
+
+        let deck = Deck::with_jokers(2, &mut thread_rng())?;
+        assert_eq!(54, deck.len());
+        assert!(deck.cards().filter(|card| card.is_joker()).count() == 2);
+        assert!(Deck::with_jokers(3, &mut thread_rng()).is_err());
+
+        // Only the show counts them
+        assert!(Hand::from_slice(&parse_cards("5h 5d 5s Jk")?).is_err());
+        assert!(Pegging::new(vec![parse_cards("5h Jk")?], 0).is_err());
+        Ok(())
+    }
+
     #[test]
     fn parse_cards_tests() -> Result<()> {
         // Accepts any suits
@@ -439,6 +634,18 @@ mod tests {
         assert_error("2g", "Invalid suit: g, must be one of: shdc");
         assert_error("2", "unexpected end, missing suit after 2");
 
+        let error_position = |cards| {
+            parse_cards(cards)
+                .unwrap_err()
+                .downcast_ref::<ParseError>()
+                .map(|err| err.position)
+        };
+
+        assert_eq!(Some(0), error_position("Fc"));
+        assert_eq!(Some(4), error_position("Ac 2g"));
+        assert_eq!(Some(7), error_position("Ac  2h Xd"));
+        assert_eq!(Some(4), error_position("Ac 2"));
+
         Ok(())
     }
 
@@ -527,6 +734,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn score_three_cards_tests() -> Result<()> {
+        let score = |cards: &str, starter: &str| -> Result<u8> {
+            let cards = parse_cards(cards)?;
+            Ok(score_three_cards(cards[..].try_into()?, card(starter)?))
+        };
+
+        // Fifteens with 2 and 3 cards
+        assert_eq!(4, score("5d Kh 2s", "8c")?);
+        // Fifteen with all 4 cards, counted once
+        assert_eq!(2, score("1d 2s 4h", "8c")?);
+        // Pair royal, double pair royal
+        assert_eq!(6, score("7d 7s 7h", "Kc")?);
+        assert_eq!(12, score("2d 2s 2h", "2c")?);
+        // Double run
+        assert_eq!(8, score("8h 9d Tc", "9h")?);
+        // 3-card flush, with the starter
+        assert_eq!(3, score("2s 4s Qs", "8h")?);
+        assert_eq!(4, score("2s 4s Qs", "8s")?);
+        // Knob
+        assert_eq!(1, score("1s 6h Jc", "Qc")?);
+        // The best 3-card hand
+        assert_eq!(15, score("5s 5h Jd", "5d")?);
+
+        Ok(())
+    }
+
     fn hand(input: &str) -> Result<Hand> {
         let cards = parse_cards(input)?;
         Ok(Hand {
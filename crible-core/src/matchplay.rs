@@ -0,0 +1,280 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{play_game, Game, GameRecord, Player, Skunk};
+
+/// When a match is over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchLength {
+    /// Won by the first player to win a majority of the games, skunks counting as one win.
+    BestOf(u32),
+    /// Won by the first player to score this many game points: 1 per win, 2 for a skunk and 3 for
+    /// a double skunk.
+    FirstTo(u32),
+}
+
+impl Display for MatchLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchLength::BestOf(n) => write!(f, "best-of:{n}"),
+            MatchLength::FirstTo(n) => write!(f, "first-to:{n}"),
+        }
+    }
+}
+
+impl FromStr for MatchLength {
+    type Err = anyhow::Error;
+
+    /// Parse `best-of:N` or `first-to:N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, n) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid match length {s}, expected best-of:N or first-to:N"))?;
+        let n = n
+            .parse()
+            .map_err(|_| anyhow!("invalid number of games: {n}"))?;
+        match kind {
+            "best-of" => Ok(MatchLength::BestOf(n)),
+            "first-to" => Ok(MatchLength::FirstTo(n)),
+            _ => Err(anyhow!(
+                "invalid match length {s}, expected best-of:N or first-to:N"
+            )),
+        }
+    }
+}
+
+/// A finished game of a match.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MatchGame {
+    pub record: GameRecord,
+    pub winner: usize,
+    pub skunk: Skunk,
+}
+
+impl MatchGame {
+    pub fn game_points(&self) -> u8 {
+        self.skunk.game_points()
+    }
+}
+
+/// A match between two players: games are played until one of them wins under `MatchLength`.
+///
+/// Players cut for the first deal, then the loser of each game deals first in the next. Game
+/// seeds are drawn from the match seed, so a match replays the same way with the same players.
+pub struct Match {
+    length: MatchLength,
+    rng: StdRng,
+    games: Vec<MatchGame>,
+}
+
+impl Match {
+    pub fn new(length: MatchLength, seed: u64) -> Result<Self> {
+        match length {
+            MatchLength::BestOf(n) if n % 2 == 0 => {
+                return Err(anyhow!("a best of {n} can end in a tie, use an odd number"))
+            }
+            MatchLength::FirstTo(0) => return Err(anyhow!("a match needs at least 1 point")),
+            _ => {}
+        }
+
+        Ok(Self {
+            length,
+            rng: StdRng::seed_from_u64(seed),
+            games: Vec::new(),
+        })
+    }
+
+    pub fn length(&self) -> MatchLength {
+        self.length
+    }
+
+    /// Games finished so far, in order.
+    pub fn games(&self) -> &[MatchGame] {
+        &self.games
+    }
+
+    /// Games won by each player.
+    pub fn wins(&self) -> [u32; 2] {
+        let mut wins = [0; 2];
+        for game in &self.games {
+            wins[game.winner] += 1;
+        }
+        wins
+    }
+
+    /// Game points scored by each player.
+    pub fn points(&self) -> [u32; 2] {
+        let mut points = [0; 2];
+        for game in &self.games {
+            points[game.winner] += game.game_points() as u32;
+        }
+        points
+    }
+
+    /// Score of the match under its length: games won for a best of, game points otherwise.
+    pub fn score(&self) -> [u32; 2] {
+        match self.length {
+            MatchLength::BestOf(_) => self.wins(),
+            MatchLength::FirstTo(_) => self.points(),
+        }
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        let target = match self.length {
+            MatchLength::BestOf(n) => n / 2 + 1,
+            MatchLength::FirstTo(n) => n,
+        };
+        self.score().iter().position(|score| *score >= target)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    /// Deal the next game, or `None` once the match is won.
+    pub fn next_game(&mut self) -> Option<Game> {
+        if self.is_over() {
+            return None;
+        }
+
+        let seed = self.rng.gen();
+        Some(match self.games.last() {
+            Some(last) => Game::with_dealer(seed, 1 - last.winner),
+            None => Game::new(seed),
+        })
+    }
+
+    /// Count a game dealt by `next_game`, once won. Its record is tagged with the match length and
+    /// the game number.
+    pub fn finish(&mut self, game: Game) -> Result<()> {
+        if self.is_over() {
+            return Err(anyhow!("the match is already won"));
+        }
+        let (Some(winner), Some(skunk)) = (game.winner(), game.board().skunk()) else {
+            return Err(anyhow!("the game isn't over"));
+        };
+
+        let mut record = game.record().clone();
+        record.set_tag("Match", &self.length.to_string());
+        record.set_tag("Game", &(self.games.len() + 1).to_string());
+        self.games.push(MatchGame {
+            record,
+            winner,
+            skunk,
+        });
+        Ok(())
+    }
+
+    /// Play the rest of the match, calling `on_game` after each game. Returns the winner.
+    pub fn play(
+        &mut self,
+        players: [&dyn Player; 2],
+        mut on_game: impl FnMut(&MatchGame),
+    ) -> Result<usize> {
+        while let Some(mut game) = self.next_game() {
+            play_game(&mut game, &players)?;
+            self.finish(game)?;
+            on_game(self.games.last().expect("a game was just finished"));
+        }
+
+        Ok(self.winner().expect("the match is over"))
+    }
+}
+
+impl Display for Match {
+    /// The score, the winner's first, then one line per game.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let score = self.score();
+        match self.winner() {
+            Some(winner) => writeln!(
+                f,
+                "Player {winner} wins {}-{} ({})",
+                score[winner],
+                score[1 - winner],
+                self.length
+            )?,
+            None => writeln!(f, "Score {}-{} ({})", score[0], score[1], self.length)?,
+        }
+        for (n, game) in self.games.iter().enumerate() {
+            let scores = game.record.scores();
+            let skunk = match game.skunk {
+                Skunk::None => "",
+                Skunk::Single => ", skunk",
+                Skunk::Double => ", double skunk",
+            };
+            writeln!(
+                f,
+                "Game {}: player {} deals first, {}-{}{skunk}",
+                n + 1,
+                game.record.dealer,
+                scores[0],
+                scores[1]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bot, RandomPlayer};
+
+    #[test]
+    fn parse_length() -> Result<()> {
+        assert_eq!(MatchLength::BestOf(3), "best-of:3".parse()?);
+        assert_eq!(MatchLength::FirstTo(7), "first-to:7".parse()?);
+        assert!("best-of".parse::<MatchLength>().is_err());
+        assert!("most-of:3".parse::<MatchLength>().is_err());
+        assert!(Match::new(MatchLength::BestOf(4), 0).is_err());
+        assert!(Match::new(MatchLength::FirstTo(0), 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn loser_deals_next() -> Result<()> {
+        let random = RandomPlayer::new(2);
+        let mut game_match = Match::new(MatchLength::FirstTo(5), 7)?;
+        let winner = game_match.play([&Bot, &random], |_| {})?;
+
+        assert_eq!(Some(winner), game_match.winner());
+        assert!(game_match.points()[winner] >= 5);
+        assert!(game_match.points()[1 - winner] < 5);
+        for (last, next) in game_match.games().iter().zip(&game_match.games()[1..]) {
+            assert_eq!(1 - last.winner, next.record.dealer);
+        }
+        assert_eq!(Some("2"), game_match.games()[1].record.tag("Game"));
+
+        assert!(game_match.next_game().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn best_of_counts_games() -> Result<()> {
+        let random = RandomPlayer::new(3);
+        let mut game_match = Match::new(MatchLength::BestOf(3), 1)?;
+        let winner = game_match.play([&random, &Bot], |_| {})?;
+
+        assert_eq!(2, game_match.wins()[winner]);
+        assert!(game_match.games().len() <= 3);
+        assert_eq!(game_match.wins(), game_match.score());
+
+        let text = game_match.to_string();
+        assert!(text.starts_with(&format!("Player {winner} wins 2-")));
+        assert_eq!(game_match.games().len() + 1, text.lines().count());
+        Ok(())
+    }
+
+    #[test]
+    fn unfinished_games_are_rejected() -> Result<()> {
+        let mut game_match = Match::new(MatchLength::BestOf(1), 0)?;
+        let game = game_match.next_game().unwrap();
+        assert!(game_match.finish(game).is_err());
+        assert!(game_match.games().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,481 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use strum::VariantArray;
+
+use crate::{no_jokers, Card, Number};
+
+/// Reason for points scored during the play.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Peg {
+    Fifteen,
+    ThirtyOne,
+    /// Pair, pair royal or double pair royal, with the number of cards involved.
+    Pairs(u8),
+    /// Run with its length.
+    Run(u8),
+    Go,
+    LastCard,
+}
+
+impl Peg {
+    pub fn points(&self) -> u8 {
+        match self {
+            Peg::Fifteen | Peg::ThirtyOne => 2,
+            Peg::Pairs(n) => n * (n - 1),
+            Peg::Run(n) => *n,
+            Peg::Go | Peg::LastCard => 1,
+        }
+    }
+}
+
+impl Display for Peg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Peg::Fifteen => write!(f, "fifteen"),
+            Peg::ThirtyOne => write!(f, "thirty-one"),
+            Peg::Pairs(2) => write!(f, "pair"),
+            Peg::Pairs(3) => write!(f, "pair royal"),
+            Peg::Pairs(_) => write!(f, "double pair royal"),
+            Peg::Run(n) => write!(f, "run of {n}"),
+            Peg::Go => write!(f, "go"),
+            Peg::LastCard => write!(f, "last card"),
+        }
+    }
+}
+
+impl FromStr for Peg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fifteen" => Ok(Peg::Fifteen),
+            "thirty-one" => Ok(Peg::ThirtyOne),
+            "pair" => Ok(Peg::Pairs(2)),
+            "pair royal" => Ok(Peg::Pairs(3)),
+            "double pair royal" => Ok(Peg::Pairs(4)),
+            "go" => Ok(Peg::Go),
+            "last card" => Ok(Peg::LastCard),
+            _ => match s.strip_prefix("run of ").map(str::parse) {
+                Some(Ok(n)) if (3..=7).contains(&n) => Ok(Peg::Run(n)),
+                _ => Err(anyhow!("unknown points: {s}")),
+            },
+        }
+    }
+}
+
+/// Points scored by the last card of `pile`, the cards played since the count was last reset.
+///
+/// Panics on jokers, which can't be played: `Pegging` rejects them.
+pub fn score_play(pile: &[Card]) -> Vec<Peg> {
+    let mut pegs = Vec::new();
+    let Some(last) = pile.last() else {
+        return pegs;
+    };
+
+    match pile.iter().map(Card::value).sum::<u8>() {
+        15 => pegs.push(Peg::Fifteen),
+        31 => pegs.push(Peg::ThirtyOne),
+        _ => {}
+    }
+
+    let same = pile
+        .iter()
+        .rev()
+        .take_while(|card| card.number() == last.number())
+        .count() as u8;
+    if same >= 2 {
+        pegs.push(Peg::Pairs(same));
+    }
+
+    if let Some(len) = longest_run(pile) {
+        pegs.push(Peg::Run(len));
+    }
+
+    pegs
+}
+
+/// Length of the longest run formed by the last 3+ cards, in any order.
+fn longest_run(pile: &[Card]) -> Option<u8> {
+    (3..=pile.len()).rev().find_map(|len| {
+        let mut seen = [false; Number::VARIANTS.len()];
+        for card in &pile[pile.len() - len..] {
+            let idx = card.number() as usize;
+            if seen[idx] {
+                return None;
+            }
+            seen[idx] = true;
+        }
+
+        let first = seen.iter().position(|seen| *seen)?;
+        seen[first..first + len]
+            .iter()
+            .all(|seen| *seen)
+            .then_some(len as u8)
+    })
+}
+
+/// A single action of the play.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PegAction {
+    Play(Card),
+    Go,
+}
+
+/// State of the play (pegging) for one hand.
+///
+/// Players take turns from the one left of the dealer. A player who can't play without going
+/// over 31 says "go"; the last player to lay a card then scores the go and the count restarts.
+#[derive(Clone, Debug)]
+pub struct Pegging {
+    hands: Vec<Vec<Card>>,
+    /// Cards played since the count was last reset.
+    pile: Vec<Card>,
+    /// Every action of the hand, in order.
+    history: Vec<(usize, PegAction)>,
+    /// Count when each action of `history` was taken.
+    counts: Vec<u8>,
+    passed: Vec<bool>,
+    last_player: Option<usize>,
+    next: usize,
+}
+
+impl Pegging {
+    /// Start the play with the hands kept by each player; `first` leads. Jokers can't be played.
+    pub fn new(hands: Vec<Vec<Card>>, first: usize) -> Result<Self> {
+        for hand in &hands {
+            no_jokers(hand)?;
+        }
+        let players = hands.len();
+        let mut pegging = Self {
+            hands,
+            pile: Vec::new(),
+            history: Vec::new(),
+            counts: Vec::new(),
+            passed: vec![false; players],
+            last_player: None,
+            next: first,
+        };
+        pegging.next = pegging.next_from(first).unwrap_or(first);
+        Ok(pegging)
+    }
+
+    pub fn players(&self) -> usize {
+        self.hands.len()
+    }
+
+    /// Same play, with `hand` in place of `player`'s cards, e.g. to try out a guess of the
+    /// cards an opponent holds.
+    pub fn with_hand(&self, player: usize, hand: Vec<Card>) -> Result<Self> {
+        no_jokers(&hand)?;
+        let mut pegging = self.clone();
+        pegging.hands[player] = hand;
+        Ok(pegging)
+    }
+
+    /// Cards player `player` has yet to play.
+    pub fn hand(&self, player: usize) -> &[Card] {
+        &self.hands[player]
+    }
+
+    pub fn pile(&self) -> &[Card] {
+        &self.pile
+    }
+
+    pub fn history(&self) -> &[(usize, PegAction)] {
+        &self.history
+    }
+
+    /// Count when each action of `history` was taken, e.g. the count a player said go at.
+    pub fn history_counts(&self) -> &[u8] {
+        &self.counts
+    }
+
+    /// Every card played so far this hand, in order.
+    pub fn played(&self) -> impl Iterator<Item = (usize, Card)> + '_ {
+        self.history
+            .iter()
+            .filter_map(|(player, action)| match action {
+                PegAction::Play(card) => Some((*player, *card)),
+                PegAction::Go => None,
+            })
+    }
+
+    pub fn count(&self) -> u8 {
+        self.pile.iter().map(Card::value).sum()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.hands.iter().all(Vec::is_empty)
+    }
+
+    /// Player who must act next, `None` once every card is played.
+    pub fn to_play(&self) -> Option<usize> {
+        (!self.is_over()).then_some(self.next)
+    }
+
+    /// Cards `player` can lay without going over 31.
+    pub fn playable(&self, player: usize) -> impl Iterator<Item = Card> + '_ {
+        let count = self.count();
+        self.hands[player]
+            .iter()
+            .copied()
+            .filter(move |card| count + card.value() <= 31)
+    }
+
+    pub fn can_play(&self, player: usize) -> bool {
+        self.playable(player).next().is_some()
+    }
+
+    /// Apply `action` for `player`, returning who scored what.
+    pub fn act(&mut self, player: usize, action: PegAction) -> Result<Vec<(usize, Peg)>> {
+        if self.to_play() != Some(player) {
+            return Err(anyhow!("not player {player}'s turn"));
+        }
+
+        match action {
+            PegAction::Play(card) => self.play(player, card),
+            PegAction::Go => self.go(player),
+        }
+    }
+
+    fn play(&mut self, player: usize, card: Card) -> Result<Vec<(usize, Peg)>> {
+        let Some(idx) = self.hands[player].iter().position(|c| *c == card) else {
+            return Err(anyhow!("{card} is not in player {player}'s hand"));
+        };
+        if self.count() + card.value() > 31 {
+            return Err(anyhow!("{card} would take the count over 31"));
+        }
+
+        self.hands[player].remove(idx);
+        self.counts.push(self.count());
+        self.pile.push(card);
+        self.history.push((player, PegAction::Play(card)));
+        self.last_player = Some(player);
+
+        let mut pegs: Vec<(usize, Peg)> = score_play(&self.pile)
+            .into_iter()
+            .map(|peg| (player, peg))
+            .collect();
+
+        if self.count() == 31 {
+            self.reset(player);
+        } else if self.is_over() {
+            pegs.push((player, Peg::LastCard));
+        } else {
+            pegs.extend(self.advance(player + 1));
+        }
+
+        Ok(pegs)
+    }
+
+    fn go(&mut self, player: usize) -> Result<Vec<(usize, Peg)>> {
+        if self.can_play(player) {
+            return Err(anyhow!("player {player} can play, go is not allowed"));
+        }
+
+        self.passed[player] = true;
+        self.counts.push(self.count());
+        self.history.push((player, PegAction::Go));
+
+        Ok(self.advance(player + 1).into_iter().collect())
+    }
+
+    /// Hand the turn to the next player still in the count, starting at `from`. When there is
+    /// none, the last player to lay a card scores the go.
+    fn advance(&mut self, from: usize) -> Option<(usize, Peg)> {
+        match self.next_from(from) {
+            Some(next) => {
+                self.next = next;
+                None
+            }
+            None => {
+                let last_player = self.last_player?;
+                self.reset(last_player);
+                Some((last_player, Peg::Go))
+            }
+        }
+    }
+
+    /// Restart the count after a 31 or a go, `last_player` played the last card.
+    fn reset(&mut self, last_player: usize) {
+        self.pile.clear();
+        self.passed.iter_mut().for_each(|passed| *passed = false);
+        self.next = self.next_from(last_player + 1).unwrap_or(last_player);
+    }
+
+    /// First player from `from` (wrapping around) who is still in the current count.
+    fn next_from(&self, from: usize) -> Option<usize> {
+        let players = self.players();
+        (0..players)
+            .map(|offset| (from + offset) % players)
+            .find(|player| !self.passed[*player] && !self.hands[*player].is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn pegs(pile: &str) -> Vec<Peg> {
+        score_play(&parse_cards(pile).unwrap())
+    }
+
+    #[test]
+    fn score_play_tests() {
+        assert_eq!(Vec::<Peg>::new(), pegs("Kh"));
+        assert_eq!(vec![Peg::Fifteen], pegs("Kh 5s"));
+        assert_eq!(vec![Peg::ThirtyOne], pegs("Kh Qs 7h 4c"));
+
+        // Pairs only count when adjacent
+        assert_eq!(vec![Peg::Pairs(2)], pegs("7h 7s"));
+        assert_eq!(vec![Peg::Pairs(3)], pegs("2h 2s 2d"));
+        assert_eq!(vec![Peg::Pairs(4)], pegs("2h 2s 2d 2c"));
+        assert_eq!(Vec::<Peg>::new(), pegs("7h 8s 7d"));
+        assert_eq!(vec![Peg::Pairs(2)], pegs("5h Ts 5d 5c"));
+        assert_eq!(vec![Peg::Fifteen, Peg::Pairs(3)], pegs("5h 5d 5c"));
+
+        // Runs in any order, as long as the last cards are all in it
+        assert_eq!(vec![Peg::Run(3)], pegs("4h 2s 3d"));
+        assert_eq!(vec![Peg::Fifteen, Peg::Run(5)], pegs("3c 1h 5s 2d 4c"));
+        assert_eq!(vec![Peg::Run(4)], pegs("5h 4s 6d 5c 7c"));
+        assert_eq!(vec![Peg::Pairs(2)], pegs("4h 2s 3d 3c"));
+        assert_eq!(vec![Peg::Run(3)], pegs("9h 4s 2d 3c"));
+
+        assert_eq!(2, Peg::Pairs(2).points());
+        assert_eq!(6, Peg::Pairs(3).points());
+        assert_eq!(12, Peg::Pairs(4).points());
+    }
+
+    fn play(pegging: &mut Pegging, player: usize, card: &str) -> Vec<(usize, Peg)> {
+        let card = parse_cards(card).unwrap()[0];
+        pegging.act(player, PegAction::Play(card)).unwrap()
+    }
+
+    fn go(pegging: &mut Pegging, player: usize) -> Vec<(usize, Peg)> {
+        pegging.act(player, PegAction::Go).unwrap()
+    }
+
+    #[test]
+    fn pegging_round() {
+        let hands = vec![
+            parse_cards("Kh Qh 5d 2c").unwrap(),
+            parse_cards("Ks 9s 8c 7c").unwrap(),
+        ];
+        let mut pegging = Pegging::new(hands, 1).unwrap();
+
+        assert_eq!(Some(1), pegging.to_play());
+        assert!(play(&mut pegging, 1, "Ks").is_empty());
+        assert_eq!(vec![(0, Peg::Fifteen)], play(&mut pegging, 0, "5d"));
+        assert!(play(&mut pegging, 1, "9s").is_empty());
+        assert!(play(&mut pegging, 0, "2c").is_empty());
+        assert_eq!(26, pegging.count());
+
+        // Nobody can play: go to player 0, who laid the last card
+        assert!(pegging.act(0, PegAction::Go).is_err());
+        assert!(go(&mut pegging, 1).is_empty());
+        assert_eq!(vec![(0, Peg::Go)], go(&mut pegging, 0));
+        assert_eq!(0, pegging.count());
+        assert_eq!(&[0, 10, 15, 24, 26, 26], pegging.history_counts());
+
+        // The player after the one who scored the go leads
+        assert_eq!(Some(1), pegging.to_play());
+        assert!(play(&mut pegging, 1, "8c").is_empty());
+        assert!(play(&mut pegging, 0, "Kh").is_empty());
+        assert!(play(&mut pegging, 1, "7c").is_empty());
+        // 25: player 1 is out of cards, so the go is scored as soon as player 0 says it
+        assert_eq!(vec![(1, Peg::Go)], go(&mut pegging, 0));
+
+        assert_eq!(Some(0), pegging.to_play());
+        assert_eq!(vec![(0, Peg::LastCard)], play(&mut pegging, 0, "Qh"));
+        assert!(pegging.is_over());
+        assert_eq!(None, pegging.to_play());
+        assert_eq!(8, pegging.played().count());
+    }
+
+    #[test]
+    fn thirty_one() {
+        let hands = vec![
+            parse_cards("Kh 6d 3c").unwrap(),
+            parse_cards("Ks 5s Ac").unwrap(),
+        ];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+
+        play(&mut pegging, 0, "Kh");
+        play(&mut pegging, 1, "Ks");
+        play(&mut pegging, 0, "6d");
+        assert_eq!(vec![(1, Peg::ThirtyOne)], play(&mut pegging, 1, "5s"));
+        assert_eq!(0, pegging.count());
+        assert_eq!(Some(0), pegging.to_play());
+    }
+
+    #[test]
+    fn go_then_keep_playing() {
+        let hands = vec![
+            parse_cards("Kh Qh 3d").unwrap(),
+            parse_cards("Ks 2s 2c").unwrap(),
+        ];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+
+        play(&mut pegging, 0, "Kh");
+        play(&mut pegging, 1, "Ks");
+        play(&mut pegging, 0, "Qh");
+        // 30: player 1 says go, player 0 can't play either and gets the go
+        assert_eq!(Vec::<(usize, Peg)>::new(), go(&mut pegging, 1));
+        assert_eq!(vec![(0, Peg::Go)], go(&mut pegging, 0));
+
+        // Player 1 leads the new count
+        assert_eq!(Some(1), pegging.to_play());
+        play(&mut pegging, 1, "2s");
+        play(&mut pegging, 0, "3d");
+        assert_eq!(vec![(1, Peg::LastCard)], play(&mut pegging, 1, "2c"));
+    }
+
+    #[test]
+    fn go_awarded_when_other_player_cant_continue() {
+        let hands = vec![
+            parse_cards("Kh Qh Jh 4d").unwrap(),
+            parse_cards("Ks 9s").unwrap(),
+        ];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+
+        play(&mut pegging, 0, "Kh");
+        play(&mut pegging, 1, "Ks");
+        assert!(pegging.act(1, PegAction::Go).is_err());
+        play(&mut pegging, 0, "4d");
+        // 24: player 1 can't play the 9s
+        assert!(go(&mut pegging, 1).is_empty());
+        assert_eq!(Some(0), pegging.to_play());
+        // Player 0 can't play either
+        assert_eq!(vec![(0, Peg::Go)], go(&mut pegging, 0));
+        assert_eq!(Some(1), pegging.to_play());
+        assert_eq!(3, pegging.played().count());
+    }
+
+    #[test]
+    fn go_among_three() {
+        let hands = vec![
+            parse_cards("Kh 9d").unwrap(),
+            parse_cards("Qs 8c").unwrap(),
+            parse_cards("Jc 2h").unwrap(),
+        ];
+        let mut pegging = Pegging::new(hands, 0).unwrap();
+
+        play(&mut pegging, 0, "Kh");
+        play(&mut pegging, 1, "Qs");
+        play(&mut pegging, 2, "Jc");
+        // 30: nobody can play, the go goes to the last player to lay a card
+        assert!(go(&mut pegging, 0).is_empty());
+        assert_eq!(Some(1), pegging.to_play());
+        assert!(go(&mut pegging, 1).is_empty());
+        assert_eq!(vec![(2, Peg::Go)], go(&mut pegging, 2));
+
+        // The player on their left leads the new count
+        assert_eq!(Some(0), pegging.to_play());
+        play(&mut pegging, 0, "9d");
+        play(&mut pegging, 1, "8c");
+        assert_eq!(vec![(2, Peg::LastCard)], play(&mut pegging, 2, "2h"));
+    }
+}
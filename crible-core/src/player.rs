@@ -0,0 +1,454 @@
+use anyhow::{anyhow, Result};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::{
+    score_cards, Card, Deck, Game, Objective, PegAction, Pegging, Phase, RuleSet, ShowRules,
+    GAME_POINTS, MAX_BID,
+};
+
+/// Scores of a game, from a player's point of view.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Scores {
+    pub own: u8,
+    pub opponent: u8,
+}
+
+/// A strategy for playing cribbage: which cards to throw to the crib and which card to peg.
+///
+/// Implementations only get to see what a player at the table would see. Decisions can fail,
+/// for strategies running outside the process.
+pub trait Player {
+    /// Called before the first deal of each game.
+    fn new_game(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Bid for the crib in auction games, more than `state.high_bid()` and at most
+    /// `state.max_bid()`, or `None` to pass. Passes by default.
+    fn bid(&self, _state: &BidState) -> Result<Option<u8>> {
+        Ok(None)
+    }
+
+    /// Pick `state.discards()` cards of the hand dealt to throw to the crib.
+    fn discard(&self, state: &DiscardState) -> Result<Vec<Card>>;
+
+    /// Pick a card to play, or `None` to say go. Only called on the player's turn.
+    fn play(&self, state: &PeggingState) -> Result<Option<Card>>;
+
+    /// Points claimed for `cards`, a hand or the crib, with the `starter` when playing muggins.
+    /// Counts them right by default.
+    fn count(&self, cards: &[Card], starter: Card, rules: ShowRules) -> Result<u8> {
+        score_cards(cards, starter, rules)
+    }
+}
+
+/// What a player knows when bidding for the crib: the cards dealt to them, the bid to top and
+/// the scores.
+pub struct BidState<'a> {
+    hand: &'a [Card],
+    discards: usize,
+    high_bid: Option<u8>,
+    is_dealer: bool,
+    scores: Scores,
+}
+
+impl<'a> BidState<'a> {
+    pub fn new(
+        hand: &'a [Card],
+        discards: usize,
+        high_bid: Option<u8>,
+        is_dealer: bool,
+        scores: Scores,
+    ) -> Self {
+        Self {
+            hand,
+            discards,
+            high_bid,
+            is_dealer,
+            scores,
+        }
+    }
+
+    /// Cards dealt to the player.
+    pub fn hand(&self) -> &'a [Card] {
+        self.hand
+    }
+
+    /// Number of cards to throw once the crib is won.
+    pub fn discards(&self) -> usize {
+        self.discards
+    }
+
+    /// The opponent's bid to top, `None` when nobody bid yet: any bid from 0 takes it.
+    pub fn high_bid(&self) -> Option<u8> {
+        self.high_bid
+    }
+
+    /// Highest bid the player can make, what a crib scores at most. Bids past their score are
+    /// owed.
+    pub fn max_bid(&self) -> u8 {
+        MAX_BID
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.is_dealer
+    }
+
+    pub fn scores(&self) -> Scores {
+        self.scores
+    }
+}
+
+/// What a player knows when throwing to the crib: the cards dealt to them, whose crib it is, the
+/// scores and what they play for.
+pub struct DiscardState<'a> {
+    hand: &'a [Card],
+    discards: usize,
+    is_dealer: bool,
+    owns_crib: bool,
+    scores: Scores,
+    players: usize,
+    objective: Objective,
+    target: u8,
+}
+
+impl<'a> DiscardState<'a> {
+    pub fn new(
+        hand: &'a [Card],
+        discards: usize,
+        is_dealer: bool,
+        owns_crib: bool,
+        scores: Scores,
+        players: usize,
+    ) -> Self {
+        Self {
+            hand,
+            discards,
+            is_dealer,
+            owns_crib,
+            scores,
+            players,
+            objective: Objective::Most,
+            target: GAME_POINTS,
+        }
+    }
+
+    /// The same state in a game played for `objective`, the most points unless told otherwise.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// The same state in a game played to `target` points, `GAME_POINTS` unless told otherwise.
+    pub fn with_target(mut self, target: u8) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Cards dealt to the player.
+    pub fn hand(&self) -> &'a [Card] {
+        self.hand
+    }
+
+    /// Number of cards to throw.
+    pub fn discards(&self) -> usize {
+        self.discards
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.is_dealer
+    }
+
+    /// Whether the crib counts for the player's team: they deal, or their partner does.
+    pub fn owns_crib(&self) -> bool {
+        self.owns_crib
+    }
+
+    pub fn scores(&self) -> Scores {
+        self.scores
+    }
+
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    /// Whether the player wants the most points or, in lowball, the fewest.
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Points needed to end the game.
+    pub fn target(&self) -> u8 {
+        self.target
+    }
+}
+
+/// What a player knows during the play: their own cards, everything played so far, the starter
+/// and their own discards, but not the cards in the other hands.
+pub struct PeggingState<'a> {
+    player: usize,
+    pegging: &'a Pegging,
+    starter: Card,
+    discards: &'a [Card],
+    is_dealer: bool,
+    scores: Scores,
+    objective: Objective,
+    teams: usize,
+    rules: RuleSet,
+}
+
+impl<'a> PeggingState<'a> {
+    pub fn new(
+        player: usize,
+        pegging: &'a Pegging,
+        starter: Card,
+        discards: &'a [Card],
+        is_dealer: bool,
+        scores: Scores,
+    ) -> Self {
+        Self {
+            player,
+            pegging,
+            starter,
+            discards,
+            is_dealer,
+            scores,
+            objective: Objective::Most,
+            teams: pegging.players(),
+            rules: RuleSet::standard(),
+        }
+    }
+
+    /// The same state in a game played for `objective`, the most points unless told otherwise.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// The same state with players in `teams` teams, player `n` in team `n % teams`: partners
+    /// peg for each other. Every player is on their own unless told otherwise.
+    pub fn with_teams(mut self, teams: usize) -> Self {
+        self.teams = teams.max(1);
+        self
+    }
+
+    /// The same state in a game played by `rules`, the standard ones unless told otherwise.
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Team of `player`.
+    pub fn team(&self, player: usize) -> usize {
+        player % self.teams
+    }
+
+    /// Rules of the game, telling what each peg scores.
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn players(&self) -> usize {
+        self.pegging.players()
+    }
+
+    /// Cards the player has yet to play.
+    pub fn hand(&self) -> &'a [Card] {
+        self.pegging.hand(self.player)
+    }
+
+    /// Cards the player can lay without going over 31.
+    pub fn playable(&self) -> impl Iterator<Item = Card> + 'a {
+        self.pegging.playable(self.player)
+    }
+
+    /// Cards played since the count was last reset.
+    pub fn pile(&self) -> &'a [Card] {
+        self.pegging.pile()
+    }
+
+    pub fn count(&self) -> u8 {
+        self.pegging.count()
+    }
+
+    /// Every action of the hand so far, in order.
+    pub fn history(&self) -> &'a [(usize, PegAction)] {
+        self.pegging.history()
+    }
+
+    /// Number of cards `player` has yet to play.
+    pub fn cards_left(&self, player: usize) -> usize {
+        self.pegging.hand(player).len()
+    }
+
+    pub fn starter(&self) -> Card {
+        self.starter
+    }
+
+    /// Cards the player threw to the crib.
+    pub fn discards(&self) -> &'a [Card] {
+        self.discards
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.is_dealer
+    }
+
+    pub fn scores(&self) -> Scores {
+        self.scores
+    }
+
+    /// Whether the player wants the most points or, in lowball, the fewest.
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Cards the player hasn't seen: they are either in other hands, in the crib or in the deck.
+    pub fn unseen(&self) -> Vec<Card> {
+        let mut deck = Deck::new_shuffled();
+        deck.remove(self.hand());
+        deck.remove(self.discards);
+        deck.remove(&[self.starter]);
+        for (_, card) in self.pegging.played() {
+            deck.remove(&[card]);
+        }
+
+        let mut unseen = deck.cards().copied().collect::<Vec<_>>();
+        unseen.sort();
+        unseen
+    }
+
+    /// A guess of the whole play: the other hands are drawn at random from the unseen cards.
+    ///
+    /// Guesses stay consistent with the play so far: a player who said go at a count of 24 holds
+    /// nothing lower than an 8.
+    pub fn determinize(&self, rng: &mut impl Rng) -> Pegging {
+        let mut pool = self.unseen();
+        let mut pegging = self.pegging.clone();
+
+        for other in (0..self.players()).filter(|player| *player != self.player) {
+            let min_value = self
+                .history()
+                .iter()
+                .zip(self.pegging.history_counts())
+                .filter(|((player, action), _)| *player == other && *action == PegAction::Go)
+                .map(|(_, count)| 32 - count)
+                .max()
+                .unwrap_or(1);
+
+            let needed = self.cards_left(other);
+            let mut candidates = pool
+                .iter()
+                .copied()
+                .filter(|card| card.value() >= min_value)
+                .collect::<Vec<_>>();
+            if candidates.len() < needed {
+                candidates = pool.clone();
+            }
+
+            let hand = candidates
+                .choose_multiple(rng, needed)
+                .copied()
+                .collect::<Vec<_>>();
+            pool.retain(|card| !hand.contains(card));
+            pegging = pegging
+                .with_hand(other, hand)
+                .expect("unseen cards aren't jokers");
+        }
+
+        pegging
+    }
+}
+
+/// Play `game` to the end, `players[n]` deciding for player `n`. Returns the winning team, the
+/// winner outside partnership games.
+pub fn play_game(game: &mut Game, players: &[&dyn Player]) -> Result<usize> {
+    if players.len() != game.players() {
+        return Err(anyhow!(
+            "{} players for a game of {}",
+            players.len(),
+            game.players()
+        ));
+    }
+    for player in players {
+        player.new_game()?;
+    }
+
+    loop {
+        match game.phase() {
+            Phase::Bid => {
+                let player = game.to_bid().ok_or_else(|| anyhow!("nobody to bid"))?;
+                let bid = players[player].bid(&game.bid_state(player))?;
+                game.bid(player, bid)?;
+            }
+            Phase::Discard => {
+                for (player, strategy) in players.iter().enumerate() {
+                    let discard = strategy.discard(&game.discard_state(player))?;
+                    game.discard(player, &discard)?;
+                }
+            }
+            Phase::Pegging => {
+                let pegging = game.pegging().ok_or_else(|| anyhow!("no play under way"))?;
+                let player = pegging.to_play().ok_or_else(|| anyhow!("nobody to play"))?;
+                let state = game
+                    .pegging_state(player)
+                    .ok_or_else(|| anyhow!("no play under way"))?;
+                let action = match players[player].play(&state)? {
+                    Some(card) => PegAction::Play(card),
+                    None => PegAction::Go,
+                };
+                game.peg(player, action)?;
+            }
+            Phase::Count => {
+                let (player, _) = game.to_count().ok_or_else(|| anyhow!("nothing to count"))?;
+                let (cards, starter, rules) =
+                    game.counting().ok_or_else(|| anyhow!("nothing to count"))?;
+                let claimed = players[player].count(cards, starter, rules)?;
+                game.count(player, claimed)?;
+            }
+            Phase::Show => game.next_hand()?,
+            Phase::Over => {
+                return game
+                    .winner()
+                    .ok_or_else(|| anyhow!("game over without winner"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_hides_other_hands() -> Result<()> {
+        let mut game = Game::with_dealer(5, 0);
+        for player in 0..2 {
+            let hand = game.hand(player).to_vec();
+            game.discard(player, &[hand[0], hand[1]])?;
+        }
+
+        let state = game.pegging_state(1).unwrap();
+        assert!(!state.is_dealer());
+        assert_eq!(4, state.cards_left(0));
+        assert_eq!(game.hand(1), state.hand());
+
+        let unseen = state.unseen();
+        assert_eq!(52 - 4 - 2 - 1, unseen.len());
+        assert!(game.hand(0).iter().all(|card| unseen.contains(card)));
+        assert!(!unseen.contains(&state.starter()));
+        assert!(state.discards().iter().all(|card| !unseen.contains(card)));
+
+        let guess = state.determinize(&mut rand::thread_rng());
+        assert_eq!(state.hand(), guess.hand(1));
+        assert_eq!(4, guess.hand(0).len());
+        assert!(guess.hand(0).iter().all(|card| unseen.contains(card)));
+
+        Ok(())
+    }
+}
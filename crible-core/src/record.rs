@@ -0,0 +1,711 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::{
+    no_jokers, parse_cards, Card, CardStyle, Deck, Game, PegAction, Points, RuleSet, Variant,
+};
+
+/// Something that happened during a game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Event {
+    /// A new hand, dealt by `dealer`.
+    Hand {
+        dealer: usize,
+    },
+    Deal {
+        player: usize,
+        cards: Vec<Card>,
+    },
+    /// Points bid by `player` for the crib in auction games, `None` for a pass.
+    Bid {
+        player: usize,
+        bid: Option<u8>,
+    },
+    Discard {
+        player: usize,
+        cards: Vec<Card>,
+    },
+    /// A card dealt from the deck to the crib, for variants with too few discards to make it.
+    CribCard(Card),
+    Starter(Card),
+    Peg {
+        player: usize,
+        action: PegAction,
+    },
+    /// Points claimed by `player` counting their hand or crib, when playing muggins.
+    Count {
+        player: usize,
+        points: u8,
+    },
+    Points(Points),
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let style = CardStyle::Ascii;
+        match self {
+            Event::Hand { dealer } => write!(f, "dealer {dealer}"),
+            Event::Deal { player, cards } => write!(f, "deal {player} {}", style.cards(cards)),
+            Event::Bid {
+                player,
+                bid: Some(bid),
+            } => write!(f, "bid {player} {bid}"),
+            Event::Bid { player, bid: None } => write!(f, "bid {player} pass"),
+            Event::Discard { player, cards } => {
+                write!(f, "discard {player} {}", style.cards(cards))
+            }
+            Event::CribCard(card) => write!(f, "crib {}", style.card(*card)),
+            Event::Starter(card) => write!(f, "starter {}", style.card(*card)),
+            Event::Peg {
+                player,
+                action: PegAction::Play(card),
+            } => write!(f, "play {player} {}", style.card(*card)),
+            Event::Peg {
+                player,
+                action: PegAction::Go,
+            } => write!(f, "go {player}"),
+            Event::Count { player, points } => write!(f, "count {player} {points}"),
+            Event::Points(points) => {
+                write!(
+                    f,
+                    "points {} {} {}",
+                    points.player, points.points, points.reason
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(card) = s.strip_prefix("starter ") {
+            return match parse_cards(card)?[..] {
+                [card] => Ok(Event::Starter(card)),
+                _ => Err(anyhow!("expected 1 starter")),
+            };
+        }
+        if let Some(card) = s.strip_prefix("crib ") {
+            return match parse_cards(card)?[..] {
+                [card] => Ok(Event::CribCard(card)),
+                _ => Err(anyhow!("expected 1 card")),
+            };
+        }
+
+        let mut words = s.splitn(3, ' ');
+        let keyword = words.next().unwrap_or_default();
+        let player = words
+            .next()
+            .ok_or_else(|| anyhow!("expected a player"))?
+            .parse()
+            .map_err(|_| anyhow!("invalid player"))?;
+        let rest = words.next().unwrap_or_default();
+
+        let event = match keyword {
+            "dealer" => Event::Hand { dealer: player },
+            "deal" => Event::Deal {
+                player,
+                cards: parse_cards(rest)?,
+            },
+            "bid" => Event::Bid {
+                player,
+                bid: match rest {
+                    "pass" => None,
+                    bid => Some(bid.parse().map_err(|_| anyhow!("invalid bid"))?),
+                },
+            },
+            "discard" => Event::Discard {
+                player,
+                cards: parse_cards(rest)?,
+            },
+            "play" => match parse_cards(rest)?[..] {
+                [card] => Event::Peg {
+                    player,
+                    action: PegAction::Play(card),
+                },
+                _ => return Err(anyhow!("expected 1 card")),
+            },
+            "go" => Event::Peg {
+                player,
+                action: PegAction::Go,
+            },
+            "count" => Event::Count {
+                player,
+                points: rest.parse().map_err(|_| anyhow!("invalid points"))?,
+            },
+            "points" => {
+                let (points, reason) = rest
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("expected points and a reason"))?;
+                Event::Points(Points {
+                    player,
+                    points: points.parse().map_err(|_| anyhow!("invalid points"))?,
+                    reason: reason.parse()?,
+                })
+            }
+            _ => return Err(anyhow!("unknown event {keyword}")),
+        };
+
+        Ok(event)
+    }
+}
+
+/// Full record of a game, in a plain-text format in the spirit of PGN for chess.
+///
+/// A header of tags in brackets comes first: `Dealer`, the first dealer, is needed to replay the
+/// game, as are `Variant` for games other than the standard one and `Rules` for rules other than
+/// the variant's. `Seed` tells how the engine shuffled, 0 when the game was played over the
+/// board: replays deal the recorded cards. Others like player names are free-form. `Result` is
+/// written from the points scored. Then comes one event per line:
+///
+/// ```text
+/// [Seed "42"]
+/// [Dealer "1"]
+/// [Player0 "bot"]
+/// [Result "121-98"]
+///
+/// dealer 1
+/// deal 0 2h 4d 5s 9c Jd Kh
+/// deal 1 Ac 3c 6h 7h 8s Qd
+/// discard 0 2h Kh
+/// discard 1 Ac Qd
+/// starter Jc
+/// points 1 2 his heels
+/// play 0 5s
+/// play 1 Ac
+/// go 0
+/// points 1 1 go
+/// points 0 6 hand
+/// ```
+///
+/// Empty lines and lines starting with `#` are ignored.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub dealer: usize,
+    pub variant: Variant,
+    pub rules: RuleSet,
+    tags: Vec<(String, String)>,
+    events: Vec<Event>,
+}
+
+impl GameRecord {
+    pub fn new(seed: u64, dealer: usize) -> Self {
+        Self {
+            seed,
+            dealer,
+            variant: Variant::Standard,
+            rules: RuleSet::standard(),
+            tags: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Value of the tag `name`, other than `Seed`, `Dealer`, `Variant`, `Rules` and `Result`.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Name of each player from the `PlayerN` tags, `Player N` when untagged.
+    pub fn player_names(&self) -> Vec<String> {
+        (0..self.variant.players())
+            .map(|player| {
+                self.tag(&format!("Player{player}"))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Player {player}"))
+            })
+            .collect()
+    }
+
+    /// Name of each team, partners joined by `&`.
+    pub fn team_names(&self) -> Vec<String> {
+        let names = self.player_names();
+        (0..self.variant.teams())
+            .map(|team| {
+                (0..names.len())
+                    .filter(|player| self.variant.team(*player) == team)
+                    .map(|player| &names[player])
+                    .join(" & ")
+            })
+            .collect()
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Every points scored, in order.
+    pub fn points(&self) -> impl Iterator<Item = &Points> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            Event::Points(points) => Some(points),
+            _ => None,
+        })
+    }
+
+    /// Scores reached by each team, capped at the game's target, minus the bids paid for cribs.
+    /// Bids past the score are owed, the score staying at 0 until they are paid off.
+    pub fn scores(&self) -> Vec<u8> {
+        let mut scores = vec![0i32; self.variant.teams()];
+        let mut high_bid = None;
+        for event in &self.events {
+            match event {
+                Event::Points(points) => {
+                    let score = &mut scores[self.variant.team(points.player)];
+                    *score = (*score + points.points as i32).min(self.rules.game as i32);
+                }
+                Event::Bid {
+                    player,
+                    bid: Some(bid),
+                } => high_bid = Some((*player, *bid)),
+                Event::Bid { bid: None, .. } => {
+                    if let Some((player, bid)) = high_bid.take() {
+                        scores[self.variant.team(player)] -= bid as i32;
+                    }
+                }
+                _ => {}
+            }
+        }
+        scores.into_iter().map(|score| score.max(0) as u8).collect()
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[Seed \"{}\"]", self.seed)?;
+        writeln!(f, "[Dealer \"{}\"]", self.dealer)?;
+        if self.variant != Variant::Standard {
+            writeln!(f, "[Variant \"{}\"]", self.variant)?;
+        }
+        if self.rules != RuleSet::for_variant(self.variant) {
+            writeln!(f, "[Rules \"{}\"]", self.rules)?;
+        }
+        for (tag, value) in &self.tags {
+            writeln!(f, "[{tag} \"{value}\"]")?;
+        }
+
+        let scores = self.scores();
+        match scores.contains(&self.rules.game) {
+            true => writeln!(f, "[Result \"{}\"]", scores.iter().join("-"))?,
+            false => writeln!(f, "[Result \"*\"]")?,
+        }
+
+        writeln!(f)?;
+        for event in &self.events {
+            writeln!(f, "{event}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seed = None;
+        let mut dealer = None;
+        let mut variant = Variant::Standard;
+        let mut rules = None;
+        let mut tags = Vec::new();
+        let mut events = Vec::new();
+
+        for (n, line) in s.lines().enumerate() {
+            let at = |err: anyhow::Error| anyhow!("line {}: {err}", n + 1);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(tag) = line.strip_prefix('[') {
+                let (name, value) = tag
+                    .strip_suffix("\"]")
+                    .and_then(|tag| tag.split_once(" \""))
+                    .ok_or_else(|| at(anyhow!("expected [Name \"value\"]")))?;
+                match name {
+                    "Seed" => seed = Some(value.parse().map_err(|_| at(anyhow!("invalid seed")))?),
+                    "Dealer" => {
+                        dealer = Some(value.parse().map_err(|_| at(anyhow!("invalid dealer")))?)
+                    }
+                    "Variant" => variant = value.parse().map_err(at)?,
+                    "Rules" => rules = Some(value.parse().map_err(at)?),
+                    "Result" => {}
+                    _ => tags.push((name.to_string(), value.to_string())),
+                }
+            } else {
+                events.push(line.parse().map_err(at)?);
+            }
+        }
+
+        Ok(Self {
+            seed: seed.unwrap_or_default(),
+            dealer: dealer.ok_or_else(|| anyhow!("missing Dealer tag"))?,
+            variant,
+            rules: rules.unwrap_or_else(|| RuleSet::for_variant(variant)),
+            tags,
+            events,
+        })
+    }
+}
+
+/// Play the game of `record` again, dealing the cards it records and checking every points
+/// recorded against the engine's own. `on_event` is called after each event is checked.
+pub fn replay(record: &GameRecord, mut on_event: impl FnMut(&Event, &Game)) -> Result<Game> {
+    let decks = recorded_decks(record)?;
+    let mut game = Game::with_decks(
+        record.variant,
+        record.rules,
+        record.seed,
+        record.dealer,
+        decks,
+    )?;
+    // Points the engine scored that the record has yet to list, starting with any head start
+    let mut scored = game.log().copied().collect::<VecDeque<_>>();
+    let mut first_hand = true;
+
+    for (n, event) in record.events().iter().enumerate() {
+        let at = |err: anyhow::Error| anyhow!("event {} ({event}): {err}", n + 1);
+        let unrecorded = |scored: &VecDeque<Points>| match scored.front() {
+            Some(points) => Err(at(anyhow!("unrecorded {}", Event::Points(*points)))),
+            None => Ok(()),
+        };
+
+        match event {
+            Event::Hand { dealer } => {
+                if !first_hand {
+                    unrecorded(&scored)?;
+                    game.next_hand().map_err(at)?;
+                }
+                first_hand = false;
+                if game.dealer() != *dealer {
+                    return Err(at(anyhow!("player {} deals", game.dealer())));
+                }
+            }
+            Event::Bid { player, bid } => {
+                unrecorded(&scored)?;
+                game.bid(*player, *bid).map_err(at)?;
+            }
+            Event::Discard { player, cards } => {
+                unrecorded(&scored)?;
+                scored.extend(game.discard(*player, cards).map_err(at)?);
+            }
+            Event::Deal { .. } | Event::CribCard(_) => {}
+            Event::Starter(card) => {
+                if game.starter() != Some(*card) {
+                    return Err(at(anyhow!(
+                        "the starter is cut once every player discarded"
+                    )));
+                }
+            }
+            Event::Peg { player, action } => {
+                unrecorded(&scored)?;
+                scored.extend(game.peg(*player, *action).map_err(at)?);
+            }
+            Event::Count { player, points } => {
+                unrecorded(&scored)?;
+                scored.extend(game.count(*player, *points).map_err(at)?);
+            }
+            Event::Points(points) => match scored.pop_front() {
+                Some(actual) if actual == *points => {}
+                Some(actual) => return Err(at(anyhow!("scored {}", Event::Points(actual)))),
+                None => return Err(at(anyhow!("no points scored"))),
+            },
+        }
+
+        on_event(event, &game);
+    }
+
+    match scored.front() {
+        Some(points) => Err(anyhow!("unrecorded {}", Event::Points(*points))),
+        None => Ok(game),
+    }
+}
+
+/// The deck of each hand of `record`, drawing its cards in the order the engine deals them:
+/// each player's deal, the cards dealt to the crib, then the starter.
+fn recorded_decks(record: &GameRecord) -> Result<Vec<Deck>> {
+    let variant = record.variant;
+    if record.events.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Events of each hand, the ones before the first hand going with it
+    let mut hands: Vec<Vec<&Event>> = vec![Vec::new()];
+    for event in &record.events {
+        let hand = hands.last_mut().expect("a hand");
+        let is_hand = |event: &&Event| matches!(event, Event::Hand { .. });
+        if is_hand(&event) && hand.iter().any(is_hand) {
+            hands.push(vec![event]);
+        } else {
+            hand.push(event);
+        }
+    }
+
+    let mut decks = Vec::with_capacity(hands.len());
+    for (n, events) in hands.into_iter().enumerate() {
+        let at = |err: anyhow::Error| anyhow!("hand {}: {err}", n + 1);
+        let mut deals = vec![None; variant.players()];
+        let mut crib = Vec::new();
+        let mut starter = None;
+        let mut discards = 0;
+        for event in events {
+            match event {
+                Event::Deal { player, cards } => {
+                    let deal = deals
+                        .get_mut(*player)
+                        .ok_or_else(|| at(anyhow!("no player {player}")))?;
+                    if cards.len() != variant.dealt() {
+                        return Err(at(anyhow!(
+                            "{} cards dealt to player {player}, {} expected",
+                            cards.len(),
+                            variant.dealt()
+                        )));
+                    }
+                    if deal.replace(cards).is_some() {
+                        return Err(at(anyhow!("player {player} dealt twice")));
+                    }
+                }
+                Event::CribCard(card) => crib.push(*card),
+                Event::Starter(_) if starter.is_some() => {
+                    return Err(at(anyhow!("2 starters cut")))
+                }
+                Event::Starter(card) => starter = Some(*card),
+                Event::Discard { .. } => discards += 1,
+                _ => {}
+            }
+        }
+
+        let mut cards = Vec::new();
+        for (player, deal) in deals.into_iter().enumerate() {
+            cards.extend(deal.ok_or_else(|| at(anyhow!("no deal to player {player}")))?);
+        }
+        if crib.len() != variant.crib_from_deck() {
+            return Err(at(anyhow!(
+                "{} cards dealt to the crib, {} expected",
+                crib.len(),
+                variant.crib_from_deck()
+            )));
+        }
+        cards.extend(crib);
+        match starter {
+            Some(starter) => cards.push(starter),
+            None if discards >= variant.players() => {
+                return Err(at(anyhow!("no starter cut after the discards")))
+            }
+            None => {}
+        }
+        if !cards.iter().all_unique() {
+            return Err(at(anyhow!("the same card dealt twice")));
+        }
+        no_jokers(&cards).map_err(at)?;
+
+        decks.push(Deck::stacked(&cards));
+    }
+
+    Ok(decks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{play_game, Bot, GreedyShow, Variant};
+
+    fn played(seed: u64) -> Game {
+        let mut game = Game::new(seed);
+        play_game(&mut game, &[&Bot, &GreedyShow]).unwrap();
+        game
+    }
+
+    #[test]
+    fn records_round_trip() -> Result<()> {
+        let game = played(3);
+        let mut record = game.record().clone();
+        record.set_tag("Player0", "bot");
+
+        let text = record.to_string();
+        assert!(text.starts_with("[Seed \"3\"]\n"));
+        let scores = game.scores();
+        assert!(text.contains(&format!("[Result \"{}-{}\"]", scores[0], scores[1])));
+        assert!(!text.contains("[Variant"));
+
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(record, parsed);
+        assert_eq!(Some("bot"), parsed.tag("Player0"));
+
+        let mut events = 0;
+        let replayed = replay(&parsed, |_, _| events += 1)?;
+        assert_eq!(record.events().len(), events);
+        assert_eq!(game.scores(), replayed.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn three_player_records() -> Result<()> {
+        let mut game = Game::new_variant(Variant::ThreePlayer, 8);
+        play_game(&mut game, &[&Bot, &GreedyShow, &Bot])?;
+
+        let text = game.record().to_string();
+        assert!(text.contains("[Variant \"three-player\"]\n"));
+        assert!(text.lines().any(|line| line.starts_with("crib ")));
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(game.record(), &parsed);
+        assert_eq!(3, parsed.player_names().len());
+        assert_eq!(game.scores(), replay(&parsed, |_, _| {})?.scores());
+
+        // The variant is needed to read the deals
+        let standard: GameRecord = text.replace("[Variant \"three-player\"]\n", "").parse()?;
+        assert!(replay(&standard, |_, _| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn partnership_records() -> Result<()> {
+        let mut game = Game::new_variant(Variant::Partnership, 4);
+        play_game(&mut game, &[&Bot, &GreedyShow, &Bot, &GreedyShow])?;
+
+        let mut record = game.record().clone();
+        record.set_tag("Player0", "Ann");
+        record.set_tag("Player2", "Cy");
+        assert_eq!(vec!["Ann & Cy", "Player 1 & Player 3"], record.team_names());
+
+        let text = record.to_string();
+        let scores = game.scores();
+        assert!(text.contains(&format!("[Result \"{}-{}\"]", scores[0], scores[1])));
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(game.scores(), replay(&parsed, |_, _| {})?.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn five_card_records() -> Result<()> {
+        let mut game = Game::new_variant(Variant::FiveCard, 2);
+        play_game(&mut game, &[&Bot, &GreedyShow])?;
+
+        let text = game.record().to_string();
+        assert!(text.contains(" 3 three for last\n"));
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(game.record(), &parsed);
+        assert!(parsed.scores().contains(&61));
+        assert_eq!(game.scores(), replay(&parsed, |_, _| {})?.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn auction_records() -> Result<()> {
+        let mut game = Game::new_variant(Variant::Auction, 1);
+        play_game(&mut game, &[&Bot, &Bot])?;
+
+        let text = game.record().to_string();
+        assert!(text.contains("[Variant \"auction\"]\n"));
+        assert!(text.lines().any(|line| line.ends_with(" pass")));
+        // Bids were paid
+        assert!(text.lines().any(|line| line.starts_with("bid ")
+            && !line.ends_with(" 0")
+            && !line.ends_with("pass")));
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(game.record(), &parsed);
+        assert_eq!(game.scores(), parsed.scores());
+        assert_eq!(game.scores(), replay(&parsed, |_, _| {})?.scores());
+        Ok(())
+    }
+
+    #[test]
+    fn records_rules() -> Result<()> {
+        let mut game = Game::with_rules(Variant::Standard, RuleSet::house(), 6, 1);
+        play_game(&mut game, &[&Bot, &GreedyShow])?;
+
+        let text = game.record().to_string();
+        assert!(text.contains("[Rules \"house\"]\n"));
+        assert!(text.lines().any(|line| line.starts_with("count ")));
+        let parsed: GameRecord = text.parse()?;
+        assert_eq!(game.record(), &parsed);
+        assert_eq!(game.scores(), replay(&parsed, |_, _| {})?.scores());
+
+        // Without muggins, the counts are out of place
+        let standard: GameRecord = text.replace("[Rules \"house\"]\n", "").parse()?;
+        assert!(replay(&standard, |_, _| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn replays_the_recorded_cards() -> Result<()> {
+        let game = played(7);
+        let mut record = game.record().clone();
+        // Deals that don't come from the seed, as in a game played over the board
+        record.seed = 8;
+        let replayed = replay(&record, |_, _| {})?;
+        assert_eq!(game.scores(), replayed.scores());
+        assert_eq!(game.record().events(), replayed.record().events());
+
+        let text = "[Dealer \"0\"]\n\
+            dealer 0\n\
+            deal 0 5h 5d 5s Jc 2h 9s\n\
+            deal 1 Ac 3c 6h 7h 8s Qd\n\
+            discard 0 2h 9s\n\
+            discard 1 Ac Qd\n\
+            starter 5c\n\
+            play 1 3c\n";
+        let record: GameRecord = text.parse()?;
+        assert_eq!(0, record.seed);
+        let replayed = replay(&record, |_, _| {})?;
+        assert_eq!(parse_cards("5h 5d 5s Jc")?, replayed.hand(0));
+        assert_eq!(Some(parse_cards("5c")?[0]), replayed.starter());
+
+        // Without the starter, or with a card dealt twice
+        let record: GameRecord = text.replace("starter 5c\n", "").parse()?;
+        assert!(replay(&record, |_, _| {}).is_err());
+        let record: GameRecord = text.replace("Qd", "5h").parse()?;
+        assert!(replay(&record, |_, _| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn replay_catches_wrong_points() -> Result<()> {
+        let text = played(5).record().to_string();
+
+        let line = text
+            .lines()
+            .find(|line| line.starts_with("points"))
+            .unwrap();
+        let Event::Points(mut points) = line.parse()? else {
+            panic!("not points: {line}");
+        };
+        points.points += 1;
+        let wrong = Event::Points(points).to_string();
+        let record: GameRecord = text.replacen(line, &wrong, 1).parse()?;
+        assert!(replay(&record, |_, _| {}).is_err());
+
+        let record: GameRecord = text.replacen(&format!("{line}\n"), "", 1).parse()?;
+        assert!(replay(&record, |_, _| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("dealer 0".parse::<GameRecord>().is_err());
+        let err = "[Seed \"1\"]\n[Dealer \"0\"]\ndealer 0\nplay 1 Zz"
+            .parse::<GameRecord>()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 4:"));
+        assert!("points 0 2 fifteen".parse::<Event>().is_ok());
+        assert!("points 0 2 sixteen".parse::<Event>().is_err());
+    }
+}
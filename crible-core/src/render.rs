@@ -0,0 +1,124 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use itertools::Itertools;
+
+use crate::{Card, Hand, Suit};
+
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// How cards are drawn on a terminal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CardStyle {
+    /// Suits as letters (h, d, s, c), for terminals without the glyphs.
+    Ascii,
+    /// Suits as glyphs (♥, ♦, ♠, ♣), same as `Display`.
+    #[default]
+    Unicode,
+    /// Suits as glyphs, hearts and diamonds in red.
+    Color,
+}
+
+impl CardStyle {
+    pub fn suit(self, suit: Suit) -> &'static str {
+        match (self, suit) {
+            (CardStyle::Ascii, Suit::H) => "h",
+            (CardStyle::Ascii, Suit::D) => "d",
+            (CardStyle::Ascii, Suit::S) => "s",
+            (CardStyle::Ascii, Suit::C) => "c",
+            (_, Suit::H) => "♥",
+            (_, Suit::D) => "♦",
+            (_, Suit::S) => "♠",
+            (_, Suit::C) => "♣",
+        }
+    }
+
+    pub fn card(self, card: Card) -> StyledCard {
+        StyledCard { card, style: self }
+    }
+
+    /// Render cards separated by a space.
+    pub fn cards<'a>(self, cards: impl IntoIterator<Item = &'a Card>) -> String {
+        cards.into_iter().map(|card| self.card(*card)).join(" ")
+    }
+
+    pub fn hand(self, hand: &Hand) -> String {
+        self.cards(hand.cards())
+    }
+}
+
+impl FromStr for CardStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(CardStyle::Ascii),
+            "unicode" => Ok(CardStyle::Unicode),
+            "color" => Ok(CardStyle::Color),
+            _ => Err(anyhow!(
+                "Invalid card style: {s}, must be one of: ascii, unicode, color"
+            )),
+        }
+    }
+}
+
+/// A card rendered with a `CardStyle`.
+pub struct StyledCard {
+    card: Card,
+    style: CardStyle,
+}
+
+impl Display for StyledCard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.card.is_joker() {
+            return write!(f, "Jk");
+        }
+        let number = self.card.number();
+        let suit = self.style.suit(self.card.suit());
+
+        match (self.style, self.card.suit()) {
+            (CardStyle::Color, Suit::H | Suit::D) => write!(f, "{RED}{number}{suit}{RESET}"),
+            _ => write!(f, "{number}{suit}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+    use anyhow::Result;
+
+    #[test]
+    fn render_styles() -> Result<()> {
+        let cards = parse_cards("Ah Td 5s Kc")?;
+
+        assert_eq!("Ah Td 5s Kc", CardStyle::Ascii.cards(&cards));
+        assert_eq!("A♥ T♦ 5♠ K♣", CardStyle::Unicode.cards(&cards));
+        assert_eq!(
+            "\x1b[31mA♥\x1b[0m \x1b[31mT♦\x1b[0m 5♠ K♣",
+            CardStyle::Color.cards(&cards)
+        );
+        assert_eq!("5♠ Jk", CardStyle::Unicode.cards(&parse_cards("5s Jk")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ascii_round_trips_through_parse_cards() -> Result<()> {
+        let cards = parse_cards("Ah Td 5s Kc 9d")?;
+        assert_eq!(cards, parse_cards(&CardStyle::Ascii.cards(&cards))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_style() {
+        assert_eq!(CardStyle::Ascii, "ascii".parse().unwrap());
+        assert_eq!(CardStyle::Unicode, "unicode".parse().unwrap());
+        assert_eq!(CardStyle::Color, "color".parse().unwrap());
+        assert!("fancy".parse::<CardStyle>().is_err());
+    }
+}
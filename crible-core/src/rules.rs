@@ -0,0 +1,182 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::{Peg, ShowRules, Variant, GAME_POINTS};
+
+/// Scoring rules that differ from table to table.
+///
+/// Presets cover the standard rules of the American Cribbage Congress, five-card cribbage and a
+/// common set of house rules. Rule sets read and write as their preset's name, or as every rule
+/// spelled out, e.g. `crib-flush=yes nobs=yes heels=2 muggins=no game=121 go=1`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RuleSet {
+    /// A flush of the 4 cards of the crib counts without the starter.
+    pub crib_flush: bool,
+    /// The jack of the starter's suit scores 1 in hand or crib.
+    pub nobs: bool,
+    /// Points the dealer scores when the starter is a jack, 0 for none.
+    pub his_heels: u8,
+    /// Points missed when counting the show go to the next opponent.
+    pub muggins: bool,
+    /// Points needed to win.
+    pub game: u8,
+    /// Points for a go, 0 when only the last card scores.
+    pub go: u8,
+}
+
+impl RuleSet {
+    /// The rules of the American Cribbage Congress, in a game to 121.
+    pub fn standard() -> Self {
+        Self {
+            crib_flush: false,
+            nobs: true,
+            his_heels: 2,
+            muggins: false,
+            game: GAME_POINTS,
+            go: 1,
+        }
+    }
+
+    /// Standard rules in a game to 61.
+    pub fn five_card() -> Self {
+        Self {
+            game: Variant::FiveCard.target(),
+            ..Self::standard()
+        }
+    }
+
+    /// Standard rules, but a 4-card flush counts in the crib and missed points are lost to
+    /// muggins.
+    pub fn house() -> Self {
+        Self {
+            crib_flush: true,
+            muggins: true,
+            ..Self::standard()
+        }
+    }
+
+    /// The standard rules of `variant`, played to its target.
+    pub fn for_variant(variant: Variant) -> Self {
+        Self {
+            game: variant.target(),
+            ..Self::standard()
+        }
+    }
+
+    /// Rules for counting a hand, or the crib.
+    pub fn show(&self, crib: bool) -> ShowRules {
+        ShowRules {
+            crib,
+            crib_flush: self.crib_flush,
+            nobs: self.nobs,
+        }
+    }
+
+    /// Points `peg` scores during the play.
+    pub fn peg_points(&self, peg: Peg) -> u8 {
+        match peg {
+            Peg::Go => self.go,
+            _ => peg.points(),
+        }
+    }
+
+    fn presets() -> [(&'static str, RuleSet); 3] {
+        [
+            ("standard", Self::standard()),
+            ("five-card", Self::five_card()),
+            ("house", Self::house()),
+        ]
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl Display for RuleSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((name, _)) = Self::presets().iter().find(|(_, rules)| rules == self) {
+            return write!(f, "{name}");
+        }
+
+        let yes_no = |rule: bool| if rule { "yes" } else { "no" };
+        write!(
+            f,
+            "crib-flush={} nobs={} heels={} muggins={} game={} go={}",
+            yes_no(self.crib_flush),
+            yes_no(self.nobs),
+            self.his_heels,
+            yes_no(self.muggins),
+            self.game,
+            self.go
+        )
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = anyhow::Error;
+
+    /// A preset's name, or rules spelled out as `name=value`, the others standard.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, rules)) = Self::presets().into_iter().find(|(name, _)| *name == s) {
+            return Ok(rules);
+        }
+
+        let mut rules = Self::standard();
+        for rule in s.split_whitespace() {
+            let (name, value) = rule
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected a preset or name=value rules: {rule}"))?;
+            let invalid = || anyhow!("invalid value for {name}: {value}");
+            let yes_no = || match value {
+                "yes" => Ok(true),
+                "no" => Ok(false),
+                _ => Err(invalid()),
+            };
+            let points = || value.parse::<u8>().map_err(|_| invalid());
+
+            match name {
+                "crib-flush" => rules.crib_flush = yes_no()?,
+                "nobs" => rules.nobs = yes_no()?,
+                "heels" => rules.his_heels = points()?,
+                "muggins" => rules.muggins = yes_no()?,
+                "game" => match points()? {
+                    0 => return Err(invalid()),
+                    game => rules.game = game,
+                },
+                "go" => rules.go = points()?,
+                _ => return Err(anyhow!("unknown rule {name}")),
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_writes() -> anyhow::Result<()> {
+        for (name, rules) in RuleSet::presets() {
+            assert_eq!(name, rules.to_string());
+            assert_eq!(rules, name.parse()?);
+        }
+
+        let rules: RuleSet = "nobs=no go=0".parse()?;
+        assert!(!rules.nobs && rules.his_heels == 2);
+        assert_eq!(0, rules.peg_points(Peg::Go));
+        assert_eq!(2, rules.peg_points(Peg::Fifteen));
+        assert_eq!(rules, rules.to_string().parse()?);
+
+        assert!("game=0".parse::<RuleSet>().is_err());
+        assert!("nobs=maybe".parse::<RuleSet>().is_err());
+        assert!("pegging".parse::<RuleSet>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use strum::VariantArray;
+
+use crate::{Card, CardStyle, Number, Suit};
+
+/// What is being counted in the show and how, as far as scoring goes. `RuleSet::show` gives the
+/// rules of a game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShowRules {
+    /// Counting a crib: a flush only scores with the starter, unless `crib_flush`.
+    pub crib: bool,
+    /// A flush of the 4 cards of the crib counts without the starter.
+    pub crib_flush: bool,
+    /// The jack of the starter's suit scores 1.
+    pub nobs: bool,
+}
+
+impl ShowRules {
+    /// A hand under standard rules.
+    pub const HAND: ShowRules = ShowRules {
+        crib: false,
+        crib_flush: false,
+        nobs: true,
+    };
+    /// A crib under standard rules.
+    pub const CRIB: ShowRules = ShowRules {
+        crib: true,
+        ..ShowRules::HAND
+    };
+
+    /// Whether `Hand::score` counts the same.
+    pub(crate) fn is_standard(self) -> bool {
+        self.nobs && !self.crib_flush
+    }
+}
+
+impl Default for ShowRules {
+    fn default() -> Self {
+        Self::HAND
+    }
+}
+
+/// Score of `cards` with the `starter`, from 3 to 7 cards in all: hands of any variant, or
+/// puzzles.
+///
+/// Fifteens are counted over every subset and each run of 3 or more separately, so a 5-card hand
+/// holding 2 short runs scores both. A flush of every card in hand scores one per card, plus one
+/// with the starter; a crib only scores it with the starter.
+pub fn score_cards(cards: &[Card], starter: Card, rules: ShowRules) -> Result<u8> {
+    if !(2..=6).contains(&cards.len()) {
+        return Err(anyhow!(
+            "3 to 7 cards with the starter expected, {} given",
+            cards.len() + 1
+        ));
+    }
+    if !cards.iter().chain([&starter]).all_unique() {
+        return Err(anyhow!(
+            "the same card twice in {} {}",
+            CardStyle::Ascii.cards(cards),
+            CardStyle::Ascii.card(starter)
+        ));
+    }
+
+    match starter.is_joker() || cards.iter().any(Card::is_joker) {
+        true => Ok(assign_jokers(cards, starter, rules).0),
+        false => Ok(score_show(cards, starter, rules)),
+    }
+}
+
+/// Best score of `cards` with the `starter` when jokers stand for any card, with the cards and
+/// starter shown: each joker replaced by the card scoring the most. A joker stands for a card
+/// not otherwise shown, and 2 jokers for different cards. Ties go to the lowest cards.
+pub fn assign_jokers(cards: &[Card], starter: Card, rules: ShowRules) -> (u8, Vec<Card>, Card) {
+    let mut all = cards.to_vec();
+    all.push(starter);
+    let jokers = all.iter().positions(Card::is_joker).collect::<Vec<_>>();
+
+    // Every card a joker can stand for, lowest first
+    let candidates = Number::VARIANTS
+        .iter()
+        .cartesian_product(Suit::VARIANTS)
+        .map(|(number, suit)| Card::new(*number, *suit))
+        .filter(|card| !all.contains(card))
+        .collect::<Vec<_>>();
+
+    let mut best: Option<(u8, Vec<Card>)> = None;
+    for assignment in candidates.into_iter().permutations(jokers.len()) {
+        let mut shown = all.clone();
+        for (position, card) in jokers.iter().zip(assignment) {
+            shown[*position] = card;
+        }
+        let (starter, cards) = shown.split_last().expect("the starter");
+        let score = score_show(cards, *starter, rules);
+        if best.as_ref().is_none_or(|(best, _)| score > *best) {
+            best = Some((score, shown));
+        }
+    }
+
+    let (score, mut shown) = best.expect("a card for every joker");
+    let starter = shown.pop().expect("the starter");
+    (score, shown, starter)
+}
+
+/// `score_cards` without the checks, for hands already dealt.
+pub(crate) fn score_show(cards: &[Card], starter: Card, rules: ShowRules) -> u8 {
+    let mut all = cards.to_vec();
+    all.push(starter);
+
+    let mut count_by_numbers = [0u8; Number::VARIANTS.len()];
+    for card in &all {
+        count_by_numbers[card.number() as usize] += 1;
+    }
+
+    let knob = Card::new(Number::J, starter.suit());
+
+    score_flush(cards, starter, rules)
+        + score_fifteens(&all)
+        + score_pairs(&count_by_numbers)
+        + score_runs(&count_by_numbers)
+        + (rules.nobs && cards.contains(&knob)) as u8
+}
+
+pub(crate) fn score_flush(cards: &[Card], starter: Card, rules: ShowRules) -> u8 {
+    let Some(suit) = cards.first().map(Card::suit) else {
+        return 0;
+    };
+    if !cards.iter().all(|card| card.suit() == suit) {
+        return 0;
+    }
+
+    match (starter.suit() == suit, rules.crib && !rules.crib_flush) {
+        (true, _) => cards.len() as u8 + 1,
+        (false, false) => cards.len() as u8,
+        (false, true) => 0,
+    }
+}
+
+fn score_fifteens(cards: &[Card]) -> u8 {
+    // Subsets of the cards seen so far adding up to each total
+    let mut ways = [0u8; 16];
+    ways[0] = 1;
+    for value in cards.iter().map(|card| card.value() as usize) {
+        for total in (value..=15).rev() {
+            ways[total] += ways[total - value];
+        }
+    }
+
+    ways[15] * 2
+}
+
+fn score_pairs(count_by_numbers: &[u8]) -> u8 {
+    count_by_numbers
+        .iter()
+        .map(|count| count * count.saturating_sub(1))
+        .sum()
+}
+
+fn score_runs(count_by_numbers: &[u8]) -> u8 {
+    let mut points = 0;
+
+    // Every stretch of consecutive numbers, scored when 3 long or more
+    for stretch in count_by_numbers.split(|count| *count == 0) {
+        if stretch.len() >= 3 {
+            points += stretch.len() as u8 * stretch.iter().product::<u8>();
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::{parse_cards, Deck, Hand};
+
+    fn score(cards: &str, starter: &str, crib: bool) -> u8 {
+        let cards = parse_cards(cards).unwrap();
+        let rules = ShowRules {
+            crib,
+            ..ShowRules::HAND
+        };
+        score_show(&cards, parse_cards(starter).unwrap()[0], rules)
+    }
+
+    #[test]
+    fn five_card_hands() {
+        // Fifteens of 5 and 6 cards
+        assert_eq!(8 + 5, score("1d 2s 3h 4c 5d", "Kh", false));
+        assert_eq!(2 + 12, score("1d 1s 1h 1c 3d", "8h", false));
+        // 2 separate runs, 3 fifteens
+        assert_eq!(6 + 6, score("1d 2s 3h Jc Qd", "Kh", false));
+        // Triple run of 4
+        assert_eq!(12 + 6, score("8d 9s Th Jh Td", "Tc", false));
+        // 5- and 6-card flushes
+        assert_eq!(5, score("2s 4s 6s 8s Qs", "Kh", false));
+        assert_eq!(6, score("2s 4s 6s 8s Qs", "Ks", false));
+        assert_eq!(0, score("2s 4s 6s 8s", "Kh", true));
+    }
+
+    #[test]
+    fn house_rules() -> Result<()> {
+        let (cards, starter) = (parse_cards("2s 4s Qs 8s")?, parse_cards("Kh")?[0]);
+        let house = ShowRules {
+            crib_flush: true,
+            nobs: false,
+            ..ShowRules::CRIB
+        };
+        assert_eq!(0, score_cards(&cards, starter, ShowRules::CRIB)?);
+        assert_eq!(4, score_cards(&cards, starter, house)?);
+
+        let (cards, starter) = (parse_cards("2h 4s Qd Js")?, parse_cards("8s")?[0]);
+        assert_eq!(1, score_cards(&cards, starter, ShowRules::HAND)?);
+        assert_eq!(0, score_cards(&cards, starter, house)?);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_cards() -> Result<()> {
+        let starter = parse_cards("5c")?[0];
+        assert!(score_cards(&parse_cards("5d")?, starter, ShowRules::HAND).is_err());
+        assert!(score_cards(
+            &parse_cards("5d 5h 5s Jc 2h 9s 8s")?,
+            starter,
+            ShowRules::HAND
+        )
+        .is_err());
+        assert!(score_cards(&parse_cards("5d 5h 5c")?, starter, ShowRules::HAND).is_err());
+        assert_eq!(
+            8,
+            score_cards(&parse_cards("5d 5h")?, starter, ShowRules::HAND)?
+        );
+        assert_eq!(
+            29,
+            score_cards(&parse_cards("5d 5h 5s Jc")?, starter, ShowRules::CRIB)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn jokers_score_their_best() -> Result<()> {
+        let [joker, other] = Card::JOKERS;
+        let cards = parse_cards("5d 5h 5s Jk")?;
+        let (points, shown, starter) =
+            assign_jokers(&cards, parse_cards("5c")?[0], ShowRules::HAND);
+        assert_eq!(29, points);
+        assert_eq!(parse_cards("5d 5h 5s Jc")?, shown);
+        assert_eq!(parse_cards("5c")?[0], starter);
+
+        // A joker starter, then 2 jokers
+        assert_eq!(
+            29,
+            score_cards(&parse_cards("5d 5h 5s Jc")?, joker, ShowRules::HAND)?
+        );
+        assert_eq!(
+            29,
+            score_cards(&parse_cards("5d 5h Jk Jc")?, other, ShowRules::HAND)?
+        );
+        // No crib flush without the starter: a second 2 makes a double run and 2 fifteens
+        assert_eq!(
+            8 + 4,
+            score_cards(
+                &parse_cards("2s 3s 4s Jk")?,
+                parse_cards("Kh")?[0],
+                ShowRules::CRIB
+            )?
+        );
+
+        // Nothing to assign
+        let cards = parse_cards("2s 4s Qs 8h")?;
+        assert_eq!(
+            (
+                score("2s 4s Qs 8h", "Kh", false),
+                cards.clone(),
+                parse_cards("Kh")?[0]
+            ),
+            assign_jokers(&cards, parse_cards("Kh")?[0], ShowRules::HAND)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn agrees_with_hand_score_on_random_deals() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20_000 {
+            let mut deck = Deck::new_shuffled_with(&mut rng);
+            let hand = deck.draw_hand()?;
+            let starter = deck.draw();
+            for (crib, rules) in [(false, ShowRules::HAND), (true, ShowRules::CRIB)] {
+                assert_eq!(
+                    hand.score(starter, crib),
+                    score_cards(hand.cards(), starter, rules)?,
+                    "{hand} {starter}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn matches_hand_score() {
+        for (cards, starter) in [
+            ("5s 5h 5d Jc", "5c"),
+            ("Qs Th Jd Qc", "Qh"),
+            ("8h 9d 9c Tc", "Kh"),
+            ("1d 1s 3d 5h", "8h"),
+            ("2s 4s Qs Ks", "Ts"),
+            ("2s 4s Qs Ks", "Th"),
+        ] {
+            let hand = Hand::from_slice(&parse_cards(cards).unwrap()).unwrap();
+            let starter = parse_cards(starter).unwrap()[0];
+            for crib in [false, true] {
+                assert_eq!(
+                    hand.score(starter, crib),
+                    score_show(
+                        hand.cards(),
+                        starter,
+                        ShowRules {
+                            crib,
+                            ..ShowRules::HAND
+                        }
+                    ),
+                    "{hand} {starter}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::show::score_flush;
+use crate::{no_jokers, score_show, Card, CardStyle, Deck, Hand, ShowRules};
+
+/// Squares of a grid.
+pub const SQUARES: usize = 16;
+
+/// The 4×4 grid of cribbage squares, squares numbered from 0 row by row.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Grid {
+    squares: [Option<Card>; SQUARES],
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, square: usize) -> Option<Card> {
+        self.squares.get(square).copied().flatten()
+    }
+
+    pub fn place(&mut self, square: usize, card: Card) -> Result<()> {
+        no_jokers(&[card])?;
+        if self.cards().any(|placed| placed == card) {
+            return Err(anyhow!(
+                "{} is already in the grid",
+                CardStyle::Ascii.card(card)
+            ));
+        }
+        match self.squares.get_mut(square) {
+            Some(Some(_)) => Err(anyhow!("square {square} is taken")),
+            Some(empty) => {
+                *empty = Some(card);
+                Ok(())
+            }
+            None => Err(anyhow!("no square {square}, they go up to {}", SQUARES - 1)),
+        }
+    }
+
+    /// Squares with no card yet.
+    pub fn empty(&self) -> impl Iterator<Item = usize> + '_ {
+        self.squares.iter().positions(Option::is_none)
+    }
+
+    /// Cards placed so far.
+    pub fn cards(&self) -> impl Iterator<Item = Card> + '_ {
+        self.squares.iter().flatten().copied()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.squares.iter().all(Option::is_some)
+    }
+
+    /// The 4 rows, then the 4 columns.
+    pub fn lines(&self) -> impl Iterator<Item = [Option<Card>; 4]> + '_ {
+        let rows = (0..4).map(|row| std::array::from_fn(|n| self.squares[row * 4 + n]));
+        let columns = (0..4).map(|column| std::array::from_fn(|n| self.squares[n * 4 + column]));
+        rows.chain(columns)
+    }
+
+    /// Score of the full grid: every row and column counted as a hand with the `starter`. `None`
+    /// until the grid is full.
+    pub fn score(&self, starter: Card) -> Option<u16> {
+        if !self.is_full() {
+            return None;
+        }
+        Some(self.lines().map(|line| score_line(&line, starter)).sum())
+    }
+
+    /// Full lines counted as hands, the others for what their cards already score with the
+    /// `starter` but flushes, which need the whole line: the value of a grid still being filled.
+    fn value(&self, starter: Card) -> u16 {
+        self.lines().map(|line| score_line(&line, starter)).sum()
+    }
+}
+
+fn score_line(line: &[Option<Card>; 4], starter: Card) -> u16 {
+    let cards = line.iter().flatten().copied().collect::<Vec<_>>();
+    match cards.len() {
+        0 => 0,
+        4 => Hand::from_slice(&cards)
+            .expect("4 cards")
+            .score(starter, false) as u16,
+        _ => {
+            let rules = ShowRules::HAND;
+            (score_show(&cards, starter, rules) - score_flush(&cards, starter, rules)) as u16
+        }
+    }
+}
+
+/// A game of cribbage squares: 16 cards drawn one by one are placed in a 4×4 grid, to score the
+/// most with each row and column as a hand. The starter is cut first, so it's known while placing.
+pub struct Squares {
+    deck: Deck,
+    starter: Card,
+    grid: Grid,
+    card: Option<Card>,
+}
+
+impl Squares {
+    /// A game dealt from `seed`, the same seed dealing the same cards.
+    pub fn new(seed: u64) -> Self {
+        let mut deck = Deck::new_shuffled_with(&mut StdRng::seed_from_u64(seed));
+        let starter = deck.draw();
+        let card = Some(deck.draw());
+        Self {
+            deck,
+            starter,
+            grid: Grid::new(),
+            card,
+        }
+    }
+
+    pub fn starter(&self) -> Card {
+        self.starter
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// The card to place, `None` once the grid is full.
+    pub fn card(&self) -> Option<Card> {
+        self.card
+    }
+
+    /// Place the card drawn in `square`, and draw the next one.
+    pub fn place(&mut self, square: usize) -> Result<()> {
+        let card = self.card.ok_or_else(|| anyhow!("the grid is full"))?;
+        self.grid.place(square, card)?;
+        self.card = (!self.grid.is_full()).then(|| self.deck.draw());
+        Ok(())
+    }
+
+    /// Score of the grid, once full.
+    pub fn score(&self) -> Option<u16> {
+        self.grid.score(self.starter)
+    }
+
+    /// Squares to place the card drawn in, best first, by `advise`.
+    pub fn advise(&self, depth: usize) -> Result<Vec<Placement>> {
+        let card = self.card.ok_or_else(|| anyhow!("the grid is full"))?;
+        advise(&self.grid, self.starter, card, depth)
+    }
+}
+
+/// A square to place a card in, and the value `advise` expects from it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Placement {
+    pub square: usize,
+    pub value: f32,
+}
+
+/// Every empty square of `grid` to place `card` in, best first.
+///
+/// Squares are valued by expectimax over the cards left: each card that can be drawn next is
+/// placed in its best square, `depth` cards ahead, and the values are averaged over the draws.
+/// Grids are then valued by their full lines, plus what the others already score with the
+/// `starter`. A `depth` of 0 only looks at `card`; each card ahead costs about 500 times more.
+pub fn advise(grid: &Grid, starter: Card, card: Card, depth: usize) -> Result<Vec<Placement>> {
+    if grid.is_full() {
+        return Err(anyhow!("the grid is full"));
+    }
+    no_jokers(&[card, starter])?;
+    if card == starter
+        || grid
+            .cards()
+            .any(|placed| placed == card || placed == starter)
+    {
+        return Err(anyhow!(
+            "{} or the starter {} is already out",
+            CardStyle::Ascii.card(card),
+            CardStyle::Ascii.card(starter)
+        ));
+    }
+
+    let mut deck = Deck::new_shuffled();
+    deck.remove(&grid.cards().collect::<Vec<_>>());
+    deck.remove(&[starter, card]);
+    // Sorted, so the values don't depend on the shuffle
+    let mut unseen = deck.cards().copied().collect::<Vec<_>>();
+    unseen.sort();
+
+    let mut grid = grid.clone();
+    let mut placements = grid
+        .empty()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|square| Placement {
+            square,
+            value: place_value(&mut grid, starter, card, square, &mut unseen, depth),
+        })
+        .collect::<Vec<_>>();
+    placements.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    Ok(placements)
+}
+
+/// Value of placing `card` in `square`, the next `depth` cards drawn from `unseen` and placed at
+/// best. `grid` and `unseen` are left as they were.
+fn place_value(
+    grid: &mut Grid,
+    starter: Card,
+    card: Card,
+    square: usize,
+    unseen: &mut Vec<Card>,
+    depth: usize,
+) -> f32 {
+    grid.squares[square] = Some(card);
+
+    let value = if depth == 0 || grid.is_full() {
+        grid.value(starter) as f32
+    } else {
+        let mut total = 0.0;
+        for n in 0..unseen.len() {
+            let next = unseen.swap_remove(n);
+            let empty = grid.empty().collect::<Vec<_>>();
+            total += empty
+                .into_iter()
+                .map(|square| place_value(grid, starter, next, square, unseen, depth - 1))
+                .fold(f32::MIN, f32::max);
+            unseen.push(next);
+            let last = unseen.len() - 1;
+            unseen.swap(n, last);
+        }
+        total / unseen.len() as f32
+    };
+
+    grid.squares[square] = None;
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn grid(cards: &str) -> Grid {
+        let mut grid = Grid::new();
+        for (square, card) in parse_cards(cards).unwrap().into_iter().enumerate() {
+            grid.place(square, card).unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn scores_rows_and_columns() -> Result<()> {
+        let full = grid("Ah 2h 3h 4h Ad 2d 3d 4d As 2s 3s 4s 6c 7c 8c 9c");
+        // Rows of 12, 13 with the diamond flush, 12 and 12; columns of a pair royal each
+        assert_eq!(
+            Some(12 + 13 + 12 + 12 + 4 * 6),
+            full.score(parse_cards("Kd")?[0])
+        );
+
+        let mut partial = grid("Ah 2h 3h");
+        assert_eq!(None, partial.score(parse_cards("Kd")?[0]));
+        assert!(partial.place(3, parse_cards("2h")?[0]).is_err());
+        assert!(partial.place(2, parse_cards("5h")?[0]).is_err());
+        assert!(partial.place(SQUARES, parse_cards("5h")?[0]).is_err());
+        assert!(partial.place(3, Card::JOKERS[0]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn advises_completing_the_best_line() -> Result<()> {
+        let grid = grid("5h 5d 5s");
+        let (starter, jack) = (parse_cards("5c")?[0], parse_cards("Jc")?[0]);
+        for depth in [0, 1] {
+            let placements = advise(&grid, starter, jack, depth)?;
+            assert_eq!(SQUARES - 3, placements.len());
+            assert_eq!(3, placements[0].square);
+            assert!(placements[0].value >= 29.0);
+        }
+        assert!(advise(&grid, starter, parse_cards("5h")?[0], 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn plays_a_full_game() -> Result<()> {
+        let mut squares = Squares::new(3);
+        while squares.card().is_some() {
+            let placements = squares.advise(0)?;
+            // The last card is valued by the score it makes
+            if placements.len() == 1 {
+                let mut grid = squares.grid().clone();
+                grid.place(placements[0].square, squares.card().unwrap())?;
+                assert_eq!(
+                    grid.score(squares.starter()),
+                    Some(placements[0].value as u16)
+                );
+            }
+            squares.place(placements[0].square)?;
+        }
+
+        assert!(squares.grid().is_full());
+        assert!(squares.score().is_some());
+        assert!(squares.place(0).is_err());
+        Ok(())
+    }
+}
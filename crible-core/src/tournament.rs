@@ -0,0 +1,365 @@
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    play_game, Bot, EnginePlayer, Game, GreedyPegging, GreedyShow, IsmctsPlayer, Player,
+    RandomPlayer, Reason, Skunk,
+};
+
+/// Names of the strategies `new_player` knows. `ismcts` takes an optional iteration budget, as in
+/// `ismcts:1000`. External engines are named `engine:<command>`.
+pub const PLAYER_NAMES: [&str; 5] = ["random", "greedy-show", "greedy-pegging", "bot", "ismcts"];
+
+const ISMCTS_ITERATIONS: u32 = 200;
+
+const ELO_START: f64 = 1500.0;
+
+/// Build the strategy called `name`, seeding its random choices from `seed`.
+pub fn new_player(name: &str, seed: u64) -> Result<Box<dyn Player>> {
+    Ok(match PlayerName::parse(name)? {
+        PlayerName::Random => Box::new(RandomPlayer::new(seed)),
+        PlayerName::GreedyShow => Box::new(GreedyShow),
+        PlayerName::GreedyPegging => Box::new(GreedyPegging::new(seed)),
+        PlayerName::Bot => Box::new(Bot),
+        PlayerName::Ismcts(iterations) => Box::new(IsmctsPlayer::new(iterations, seed)),
+        PlayerName::Engine(command) => Box::new(EnginePlayer::spawn(command)?),
+    })
+}
+
+/// A name accepted by `new_player`, read without building the player: engines aren't started.
+enum PlayerName<'a> {
+    Random,
+    GreedyShow,
+    GreedyPegging,
+    Bot,
+    Ismcts(u32),
+    Engine(&'a str),
+}
+
+impl<'a> PlayerName<'a> {
+    fn parse(name: &'a str) -> Result<Self> {
+        let (base, param) = match name.split_once(':') {
+            Some((base, param)) => (base, Some(param)),
+            None => (name, None),
+        };
+
+        Ok(match (base, param) {
+            ("random", None) => Self::Random,
+            ("greedy-show", None) => Self::GreedyShow,
+            ("greedy-pegging", None) => Self::GreedyPegging,
+            ("bot", None) => Self::Bot,
+            ("engine", Some(command)) if !command.trim().is_empty() => Self::Engine(command),
+            ("ismcts", iterations) => Self::Ismcts(match iterations {
+                Some(iterations) => iterations
+                    .parse()
+                    .map_err(|_| anyhow!("invalid iteration budget: {iterations}"))?,
+                None => ISMCTS_ITERATIONS,
+            }),
+            _ => {
+                return Err(anyhow!(
+                    "unknown player {name}, expected one of: {}",
+                    PLAYER_NAMES.join(", ")
+                ))
+            }
+        })
+    }
+}
+
+/// Results of one player over a tournament.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Standing {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    /// Points pegged during the play, his heels included.
+    pub pegging: u32,
+    pub hand: u32,
+    pub crib: u32,
+    /// Games won with the loser under 91, then under 61.
+    pub skunks: u32,
+    pub double_skunks: u32,
+    /// Games lost under 91, then under 61.
+    pub skunked: u32,
+    pub double_skunked: u32,
+    pub elo: f64,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.games)
+    }
+
+    /// 95% Wilson score interval around the win rate.
+    pub fn win_interval(&self) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+
+        const Z: f64 = 1.96;
+        let n = self.games as f64;
+        let p = self.win_rate();
+        let center = p + Z * Z / (2.0 * n);
+        let margin = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+        let scale = 1.0 + Z * Z / n;
+        (
+            ((center - margin) / scale).max(0.0),
+            ((center + margin) / scale).min(1.0),
+        )
+    }
+
+    /// Average points per game scored during the play, in hands and in cribs.
+    pub fn points_per_game(&self) -> (f64, f64, f64) {
+        let games = self.games.max(1) as f64;
+        (
+            self.pegging as f64 / games,
+            self.hand as f64 / games,
+            self.crib as f64 / games,
+        )
+    }
+
+    pub fn skunk_rate(&self) -> f64 {
+        ratio(self.skunks, self.games)
+    }
+
+    pub fn skunked_rate(&self) -> f64 {
+        ratio(self.skunked, self.games)
+    }
+}
+
+/// Games won by `a` against `b`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Pairing {
+    pub a: usize,
+    pub b: usize,
+    pub games: u32,
+    pub a_wins: u32,
+}
+
+/// Outcome of a tournament: one standing per entrant, in entry order, and the head-to-head
+/// results.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TournamentResult {
+    pub standings: Vec<Standing>,
+    pub pairings: Vec<Pairing>,
+}
+
+/// A round robin between strategies: every pair of entrants plays `games` games, the first deal
+/// alternating between them.
+///
+/// Game seeds and player seeds are all drawn from the tournament seed, so a tournament replays
+/// the same way every time.
+pub struct Tournament {
+    names: Vec<String>,
+    games: u32,
+    seed: u64,
+}
+
+impl Tournament {
+    /// Entrants are names accepted by `new_player`, and may be repeated. Players are only built
+    /// once the tournament runs.
+    pub fn new(names: &[&str], games: u32, seed: u64) -> Result<Self> {
+        if names.len() < 2 {
+            return Err(anyhow!("a tournament needs at least 2 players"));
+        }
+        for name in names {
+            PlayerName::parse(name)?;
+        }
+
+        Ok(Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            games,
+            seed,
+        })
+    }
+
+    /// Play every game, calling `progress` with the number of games played so far.
+    pub fn run(&self, mut progress: impl FnMut(u32)) -> Result<TournamentResult> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut standings = self
+            .names
+            .iter()
+            .map(|name| Standing {
+                name: name.clone(),
+                elo: ELO_START,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let mut pairings = Vec::new();
+        let mut played = 0;
+
+        for a in 0..self.names.len() {
+            for b in a + 1..self.names.len() {
+                let players = [
+                    new_player(&self.names[a], rng.gen())?,
+                    new_player(&self.names[b], rng.gen())?,
+                ];
+                let mut pairing = Pairing {
+                    a,
+                    b,
+                    games: 0,
+                    a_wins: 0,
+                };
+
+                for n in 0..self.games {
+                    let mut game = Game::with_dealer(rng.gen(), n as usize % 2);
+                    let winner = play_game(&mut game, &[&*players[0], &*players[1]])?;
+                    record(&mut standings, [a, b], &game, winner);
+
+                    pairing.games += 1;
+                    pairing.a_wins += (winner == 0) as u32;
+                    played += 1;
+                    progress(played);
+                }
+
+                pairings.push(pairing);
+            }
+        }
+
+        let elos = elo(standings.len(), &pairings);
+        for (standing, elo) in standings.iter_mut().zip(elos) {
+            standing.elo = elo;
+        }
+
+        Ok(TournamentResult {
+            standings,
+            pairings,
+        })
+    }
+}
+
+fn record(standings: &mut [Standing], seats: [usize; 2], game: &Game, winner: usize) {
+    for points in game.log() {
+        let standing = &mut standings[seats[points.player]];
+        let n = points.points as u32;
+        match points.reason {
+            Reason::HisHeels | Reason::ThreeForLast | Reason::Peg(_) => standing.pegging += n,
+            Reason::Hand | Reason::Muggins => standing.hand += n,
+            Reason::Crib => standing.crib += n,
+        }
+    }
+
+    let skunk = game.board().skunk().unwrap_or(Skunk::None);
+    let (winner, loser) = (seats[winner], seats[1 - winner]);
+    for seat in seats {
+        standings[seat].games += 1;
+    }
+    standings[winner].wins += 1;
+    if skunk >= Skunk::Single {
+        standings[winner].skunks += 1;
+        standings[loser].skunked += 1;
+    }
+    if skunk == Skunk::Double {
+        standings[winner].double_skunks += 1;
+        standings[loser].double_skunked += 1;
+    }
+}
+
+/// Ratings best explaining the head-to-head results, averaging 1500.
+///
+/// Unlike game by game updates, the fit doesn't depend on the order the games were played in.
+fn elo(players: usize, pairings: &[Pairing]) -> Vec<f64> {
+    let mut ratings = vec![ELO_START; players];
+
+    for _ in 0..1000 {
+        let mut gradient = vec![0.0; players];
+        for pairing in pairings {
+            let expected =
+                1.0 / (1.0 + 10f64.powf((ratings[pairing.b] - ratings[pairing.a]) / 400.0));
+            // Half a win for each side keeps perfect scores from running off to infinity
+            let actual = (pairing.a_wins as f64 + 0.5) / (pairing.games as f64 + 1.0);
+            let delta = (actual - expected) * pairing.games as f64;
+            gradient[pairing.a] += delta;
+            gradient[pairing.b] -= delta;
+        }
+
+        let games = pairings
+            .iter()
+            .map(|pairing| pairing.games)
+            .sum::<u32>()
+            .max(1) as f64;
+        for (rating, gradient) in ratings.iter_mut().zip(gradient) {
+            *rating += 400.0 * gradient / games;
+        }
+
+        let mean = ratings.iter().sum::<f64>() / players as f64;
+        ratings
+            .iter_mut()
+            .for_each(|rating| *rating += ELO_START - mean);
+    }
+
+    ratings
+}
+
+fn ratio(n: u32, total: u32) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => n as f64 / total as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_players_are_rejected() {
+        assert!(new_player("ismcts:50", 0).is_ok());
+        assert!(new_player("ismcts:lots", 0).is_err());
+        assert!(new_player("bot:3", 0).is_err());
+        assert!(Tournament::new(&["bot", "nobody"], 1, 0).is_err());
+        assert!(Tournament::new(&["bot"], 1, 0).is_err());
+        assert!(Tournament::new(&["bot", "engine:"], 1, 0).is_err());
+
+        // Engines are only started when the tournament runs
+        let tournament = Tournament::new(&["bot", "engine:/no/such/engine"], 1, 0).unwrap();
+        assert!(tournament.run(|_| {}).is_err());
+    }
+
+    #[test]
+    fn tournaments_are_deterministic() -> Result<()> {
+        let tournament = Tournament::new(&["random", "bot", "greedy-pegging"], 6, 9)?;
+        let result = tournament.run(|_| {})?;
+        assert_eq!(result, tournament.run(|_| {})?);
+
+        assert_eq!(3, result.pairings.len());
+        for standing in &result.standings {
+            assert_eq!(12, standing.games);
+            let (low, high) = standing.win_interval();
+            assert!(low - 1e-9 <= standing.win_rate() && standing.win_rate() <= high + 1e-9);
+        }
+
+        let wins = result.standings.iter().map(|s| s.wins).sum::<u32>();
+        assert_eq!(18, wins);
+        let skunks = result.standings.iter().map(|s| s.skunks).sum::<u32>();
+        let skunked = result.standings.iter().map(|s| s.skunked).sum::<u32>();
+        assert_eq!(skunks, skunked);
+
+        Ok(())
+    }
+
+    #[test]
+    fn elo_follows_results() {
+        let pairings = [
+            Pairing {
+                a: 0,
+                b: 1,
+                games: 100,
+                a_wins: 75,
+            },
+            Pairing {
+                a: 1,
+                b: 2,
+                games: 100,
+                a_wins: 50,
+            },
+        ];
+        let ratings = elo(3, &pairings);
+
+        assert!(ratings[0] > ratings[1]);
+        assert!((ratings[1] - ratings[2]).abs() < 1.0);
+        // Winning 75% is worth about 190 points
+        assert!((ratings[0] - ratings[1] - 190.0).abs() < 10.0);
+        assert!((ratings.iter().sum::<f64>() / 3.0 - ELO_START).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::GAME_POINTS;
+
+/// How many players sit at the table and how the cards are dealt.
+///
+/// Every variant gets a crib of 4. Hands keep 4 cards and games run to 121, except in five- and
+/// seven-card cribbage. Players race to the target, except in lowball. The crib is the dealer's,
+/// unless auctioned.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Variant {
+    /// Two players, 6 cards each, 2 thrown to the crib.
+    #[default]
+    Standard,
+    /// Three players, 5 cards each, 1 thrown to the crib with 1 from the deck.
+    ThreePlayer,
+    /// Four players in 2 teams of partners sitting across: players 0 and 2 against 1 and 3. 5
+    /// cards each, 1 thrown to the crib, which belongs to the dealer's team.
+    Partnership,
+    /// The classic game: two players, 5 cards each, 2 thrown to the crib and 3 kept. The pone of
+    /// the first hand starts with 3 points, and the game is played to 61.
+    FiveCard,
+    /// Two players, 7 cards each, 2 thrown to the crib and 5 kept, played to 181.
+    SevenCard,
+    /// Dealt as the standard game, but the first to reach 121 loses: players score as few points as
+    /// they can.
+    Lowball,
+    /// Dealt as the standard game, but after the deal players bid points for the crib: the winner
+    /// pegs their bid back and takes the crib.
+    Auction,
+}
+
+/// What players aim for on the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Objective {
+    /// Score the most points: reaching the target wins.
+    #[default]
+    Most,
+    /// Score the fewest points: reaching the target loses.
+    Fewest,
+}
+
+impl Objective {
+    /// 1 when points are good for the player who scores them, -1 in lowball, to turn points into
+    /// values to maximize.
+    pub fn sign(self) -> i32 {
+        match self {
+            Objective::Most => 1,
+            Objective::Fewest => -1,
+        }
+    }
+}
+
+impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::Standard,
+        Variant::ThreePlayer,
+        Variant::Partnership,
+        Variant::FiveCard,
+        Variant::SevenCard,
+        Variant::Lowball,
+        Variant::Auction,
+    ];
+
+    pub fn players(self) -> usize {
+        match self {
+            Variant::Standard
+            | Variant::FiveCard
+            | Variant::SevenCard
+            | Variant::Lowball
+            | Variant::Auction => 2,
+            Variant::ThreePlayer => 3,
+            Variant::Partnership => 4,
+        }
+    }
+
+    /// Sides scoring on the board: teams in partnership games, otherwise every player is a team of
+    /// their own.
+    pub fn teams(self) -> usize {
+        match self {
+            Variant::Partnership => 2,
+            _ => self.players(),
+        }
+    }
+
+    /// Team of `player`.
+    pub fn team(self, player: usize) -> usize {
+        player % self.teams()
+    }
+
+    /// Cards dealt to each player.
+    pub fn dealt(self) -> usize {
+        match self {
+            Variant::Standard | Variant::Lowball | Variant::Auction => 6,
+            Variant::ThreePlayer | Variant::Partnership | Variant::FiveCard => 5,
+            Variant::SevenCard => 7,
+        }
+    }
+
+    /// Cards each player throws to the crib.
+    pub fn discards(self) -> usize {
+        match self {
+            Variant::Standard
+            | Variant::FiveCard
+            | Variant::SevenCard
+            | Variant::Lowball
+            | Variant::Auction => 2,
+            Variant::ThreePlayer | Variant::Partnership => 1,
+        }
+    }
+
+    /// Cards each player keeps for the play and the show.
+    pub fn kept(self) -> usize {
+        self.dealt() - self.discards()
+    }
+
+    /// Cards dealt from the deck straight to the crib, to make it 4.
+    pub fn crib_from_deck(self) -> usize {
+        4 - self.players() * self.discards()
+    }
+
+    /// Points needed to win a game.
+    pub fn target(self) -> u8 {
+        match self {
+            Variant::FiveCard => 61,
+            Variant::SevenCard => 181,
+            _ => GAME_POINTS,
+        }
+    }
+
+    /// Points the pone of the first hand scores before the play, "three for last", making up for
+    /// the dealer's crib in short games.
+    pub fn head_start(self) -> u8 {
+        match self {
+            Variant::FiveCard => 3,
+            _ => 0,
+        }
+    }
+
+    /// Whether players bid for the crib after each deal.
+    pub fn has_auction(self) -> bool {
+        self == Variant::Auction
+    }
+
+    /// Whether players go for the most points or, in lowball, the fewest.
+    pub fn objective(self) -> Objective {
+        match self {
+            Variant::Lowball => Objective::Fewest,
+            _ => Objective::Most,
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "standard"),
+            Variant::ThreePlayer => write!(f, "three-player"),
+            Variant::Partnership => write!(f, "partnership"),
+            Variant::FiveCard => write!(f, "five-card"),
+            Variant::SevenCard => write!(f, "seven-card"),
+            Variant::Lowball => write!(f, "lowball"),
+            Variant::Auction => write!(f, "auction"),
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variant::ALL
+            .into_iter()
+            .find(|variant| variant.to_string() == s)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown variant {s}, expected one of: {}",
+                    Variant::ALL.map(|variant| variant.to_string()).join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cribs_have_4_cards() {
+        for variant in Variant::ALL {
+            assert_eq!(
+                4,
+                variant.players() * variant.discards() + variant.crib_from_deck()
+            );
+            assert!((3..=5).contains(&variant.kept()));
+            assert_eq!(Ok(variant), variant.to_string().parse().map_err(|_| ()));
+        }
+        assert!("four-player".parse::<Variant>().is_err());
+        assert_eq!(3, Variant::FiveCard.kept());
+        assert_eq!(61, Variant::FiveCard.target());
+        assert_eq!(5, Variant::SevenCard.kept());
+        assert_eq!(Objective::Fewest, Variant::Lowball.objective());
+        assert_eq!(Variant::Standard.dealt(), Variant::Lowball.dealt());
+    }
+
+    #[test]
+    fn partners_sit_across() {
+        let partnership = Variant::Partnership;
+        assert_eq!(2, partnership.teams());
+        assert_eq!(partnership.team(0), partnership.team(2));
+        assert_eq!(partnership.team(1), partnership.team(3));
+        assert_ne!(partnership.team(0), partnership.team(1));
+        assert_eq!(2, Variant::ThreePlayer.team(2));
+    }
+}